@@ -36,6 +36,7 @@ use frame_support::{
 	parameter_types,
 	traits::{
 		fungible::{Balanced, Credit, ItemOf},
+		fungibles,
 		tokens::{nonfungibles_v2::Inspect, GetSalary, PayFromAccount},
 		AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU16, ConstU32, Contains, Currency,
 		EitherOfDiverse, EqualPrivilegeOnly, Imbalance, InsideBoth, InstanceFilter,
@@ -56,7 +57,7 @@ use frame_system::{
 pub use node_primitives::{AccountId, Signature};
 use node_primitives::{AccountIndex, Balance, BlockNumber, Hash, Moment, Nonce};
 use pallet_asset_conversion::{NativeOrAssetId, NativeOrAssetIdConverter};
-use pallet_broker::{CoreAssignment, CoreIndex, CoretimeInterface, PartsOf57600};
+use pallet_broker::{CoreAssignment, CoreIndex, CoretimeInterface, PartsOf57600, TaskId};
 use pallet_election_provider_multi_phase::SolutionAccuracyOf;
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use pallet_nfts::PalletFeatures;
@@ -1938,8 +1939,12 @@ impl pallet_statement::Config for Runtime {
 	type MaxAllowedBytes = MaxAllowedBytes;
 }
 
+/// The number of blocks in a Broker timeslice.
+const BROKER_TIMESLICE_PERIOD: BlockNumber = 2;
+
 parameter_types! {
 	pub const BrokerPalletId: PalletId = PalletId(*b"py/broke");
+	pub const BrokerPriceEmaFactor: Perbill = Perbill::from_percent(20);
 }
 
 pub struct IntoAuthor;
@@ -1951,6 +1956,15 @@ impl OnUnbalanced<Credit<AccountId, Balances>> for IntoAuthor {
 	}
 }
 
+pub struct AssetsIntoAuthor;
+impl OnUnbalanced<fungibles::Credit<AccountId, Assets>> for AssetsIntoAuthor {
+	fn on_nonzero_unbalanced(credit: fungibles::Credit<AccountId, Assets>) {
+		if let Some(author) = Authorship::author() {
+			let _ = <Assets as fungibles::Balanced<_>>::resolve(&author, credit);
+		}
+	}
+}
+
 parameter_types! {
 	pub storage CoreCount: Option<CoreIndex> = None;
 	pub storage CoretimeRevenue: Option<(BlockNumber, Balance)> = None;
@@ -1967,6 +1981,10 @@ impl CoretimeInterface for CoretimeProvider {
 	fn request_core_count(_count: CoreIndex) {}
 	fn request_revenue_info_at(_when: Self::BlockNumber) {}
 	fn credit_account(_who: Self::AccountId, _amount: Self::Balance) {}
+	fn revoke_credit(_who: Self::AccountId, _amount: Self::Balance) -> Self::Balance {
+		0
+	}
+	fn place_order(_task: TaskId, _begin: Self::BlockNumber, _amount: Self::Balance) {}
 	fn assign_core(
 		_core: CoreIndex,
 		_begin: Self::BlockNumber,
@@ -1997,16 +2015,31 @@ impl CoretimeInterface for CoretimeProvider {
 impl pallet_broker::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type OnRevenue = IntoAuthor;
-	type TimeslicePeriod = ConstU32<2>;
+	type TimeslicePeriod = ConstU32<BROKER_TIMESLICE_PERIOD>;
 	type MaxLeasedCores = ConstU32<5>;
 	type MaxReservedCores = ConstU32<5>;
+	type MaxMetadataLen = ConstU32<32>;
 	type Coretime = CoretimeProvider;
 	type ConvertBalance = traits::Identity;
+	type ConvertAccount = traits::Identity;
 	type WeightInfo = ();
 	type PalletId = BrokerPalletId;
 	type AdminOrigin = EnsureRoot<AccountId>;
 	type PriceAdapter = pallet_broker::Linear;
+	type MinPrice = ConstU128<0>;
+	type RegionDeposit = ConstU128<{ DOLLARS }>;
+	type AutoPoolUnsold = ConstBool<true>;
+	type RevenueClaimPeriod = ConstU32<{ 28 * DAYS / BROKER_TIMESLICE_PERIOD }>;
+	type CreditExpiry = ConstU32<{ 14 * DAYS / BROKER_TIMESLICE_PERIOD }>;
+	type PriceEmaFactor = BrokerPriceEmaFactor;
+	type AssignmentGrace = ConstU32<2>;
+	type Assets = Assets;
+	type OnAssetRevenue = AssetsIntoAuthor;
+	type AssetPrice = ();
+	type OnReserve = ();
+	type OnRefund = ();
 }
 
 construct_runtime!(
@@ -2137,6 +2170,7 @@ type Migrations = (
 	pallet_nomination_pools::migration::v2::MigrateToV2<Runtime>,
 	pallet_alliance::migration::Migration<Runtime>,
 	pallet_contracts::Migration<Runtime>,
+	pallet_broker::migration::v1::MigrateToV1<Runtime>,
 );
 
 type EventRecord = frame_system::EventRecord<
@@ -2550,6 +2584,46 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_broker_runtime_api::BrokerApi<Block, BlockNumber, Balance, BlockNumber> for Runtime {
+		fn effective_assignment(
+			core: pallet_broker::CoreIndex,
+			when: pallet_broker::Timeslice,
+		) -> Vec<(pallet_broker::CoreAssignment, pallet_broker::PartsOf57600)> {
+			Broker::effective_assignment(core, when)
+		}
+
+		fn sale_index() -> u32 {
+			pallet_broker::SaleIndex::<Runtime>::get()
+		}
+
+		fn sale_region_begin() -> Option<pallet_broker::Timeslice> {
+			pallet_broker::SaleInfo::<Runtime>::get().map(|sale| sale.region_begin)
+		}
+
+		fn next_sale_rotation() -> Option<BlockNumber> {
+			Broker::next_sale_rotation()
+		}
+
+		fn region_end(region_id: pallet_broker::RegionId) -> Option<pallet_broker::Timeslice> {
+			Broker::region_end(region_id)
+		}
+
+		fn coretime_price_ema() -> Option<Balance> {
+			pallet_broker::PriceEma::<Runtime>::get()
+		}
+
+		fn schedule_for_core(
+			core: pallet_broker::CoreIndex,
+			cycles: u32,
+		) -> Vec<(pallet_broker::Timeslice, Vec<(pallet_broker::CoreAssignment, pallet_broker::PartsOf57600)>)> {
+			Broker::schedule_for_core(core, cycles)
+		}
+
+		fn sale_status() -> pallet_broker::SaleStatus<BlockNumber> {
+			Broker::sale_status()
+		}
+	}
+
 	impl pallet_nfts_runtime_api::NftsApi<Block, AccountId, u32, u32> for Runtime {
 		fn owner(collection: u32, item: u32) -> Option<AccountId> {
 			<Nfts as Inspect<AccountId>>::owner(&collection, &item)
@@ -0,0 +1,64 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the FRAME Broker pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_broker::{CoreAssignment, CoreIndex, PartsOf57600, RegionId, SaleStatus, Timeslice};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait BrokerApi<RelayBlockNumber, Balance, BlockNumber>
+	where
+		RelayBlockNumber: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+	{
+		/// The assignment of `core` in effect at timeslice `when`.
+		fn effective_assignment(
+			core: CoreIndex,
+			when: Timeslice,
+		) -> Vec<(CoreAssignment, PartsOf57600)>;
+
+		/// The index of the current sale, counting from one.
+		fn sale_index() -> u32;
+
+		/// The timeslice at which the Regions of the current sale begin.
+		fn sale_region_begin() -> Option<Timeslice>;
+
+		/// The earliest Relay-chain block at which the next sale will be initialized.
+		fn next_sale_rotation() -> Option<RelayBlockNumber>;
+
+		/// The timeslice at which the coverage of the Region `region_id` ceases.
+		fn region_end(region_id: RegionId) -> Option<Timeslice>;
+
+		/// The exponential moving average of the prices at which sales concluded.
+		fn coretime_price_ema() -> Option<Balance>;
+
+		/// The assignment of `core` for each of the next `cycles` sale cycles, together with the
+		/// timeslice at which each cycle begins.
+		fn schedule_for_core(
+			core: CoreIndex,
+			cycles: u32,
+		) -> Vec<(Timeslice, Vec<(CoreAssignment, PartsOf57600)>)>;
+
+		/// The phase which the ongoing sale is in.
+		fn sale_status() -> SaleStatus<BlockNumber>;
+	}
+}
@@ -0,0 +1,44 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(missing_docs)]
+
+/// Type for determining which assets other than the native currency may be used to pay for
+/// Coretime, and how much of them is due.
+pub trait AssetPrice<AssetId, Balance, AssetBalance> {
+	/// Return the amount of `asset` equivalent to `price` of the native currency, or `None` if
+	/// `asset` may not be used as payment.
+	///
+	/// - `asset`: The asset in which payment is offered.
+	/// - `price`: The price, denominated in the native currency.
+	fn asset_price(asset: AssetId, price: Balance) -> Option<AssetBalance>;
+
+	/// Return an asset which may be used as payment, if there is any.
+	#[cfg(feature = "runtime-benchmarks")]
+	fn accepted_asset() -> Option<AssetId>;
+}
+
+/// Accept no asset other than the native currency.
+impl<AssetId, Balance, AssetBalance> AssetPrice<AssetId, Balance, AssetBalance> for () {
+	fn asset_price(_: AssetId, _: Balance) -> Option<AssetBalance> {
+		None
+	}
+	#[cfg(feature = "runtime-benchmarks")]
+	fn accepted_asset() -> Option<AssetId> {
+		None
+	}
+}
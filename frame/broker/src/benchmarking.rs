@@ -25,13 +25,13 @@ use frame_support::{
 	storage::bounded_vec::BoundedVec,
 	traits::{
 		fungible::{Inspect, Mutate},
-		EnsureOrigin, Hooks,
+		fungibles, EnsureOrigin, Hooks,
 	},
 };
 use frame_system::{Pallet as System, RawOrigin};
 use sp_arithmetic::{traits::Zero, Perbill};
 use sp_core::Get;
-use sp_runtime::Saturating;
+use sp_runtime::{traits::Convert, Saturating};
 use sp_std::{vec, vec::Vec};
 
 const SEED: u32 = 0;
@@ -55,12 +55,12 @@ fn new_config_record<T: Config>() -> ConfigRecordOf<T> {
 }
 
 fn new_schedule() -> Schedule {
-	// Max items for worst case
+	// Max items for worst case, each on its own part of the core
 	let mut items = Vec::new();
 	for i in 0..CORE_MASK_BITS {
 		items.push(ScheduleItem {
 			assignment: Task(i.try_into().unwrap()),
-			mask: CoreMask::complete(),
+			mask: CoreMask::from_chunk(i as u32, i as u32 + 1),
 		});
 	}
 	Schedule::truncate_from(items)
@@ -95,7 +95,7 @@ fn setup_and_start_sale<T: Config>() -> Result<u16, BenchmarkError> {
 	// Assume Leases to be filled for worst case
 	setup_leases::<T>(T::MaxLeasedCores::get(), 1, 10);
 
-	Broker::<T>::do_start_sales(10u32.into(), MAX_CORE_COUNT.into())
+	Broker::<T>::do_start_sales(10u32.into(), MAX_CORE_COUNT.into(), None)
 		.map_err(|_| BenchmarkError::Weightless)?;
 
 	Ok(T::MaxReservedCores::get()
@@ -196,7 +196,7 @@ mod benches {
 			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
 
 		#[extrinsic_call]
-		_(origin as T::RuntimeOrigin, initial_price, n.try_into().unwrap());
+		_(origin as T::RuntimeOrigin, initial_price, n.try_into().unwrap(), None);
 
 		assert!(SaleInfo::<T>::get().is_some());
 		assert_last_event::<T>(
@@ -229,7 +229,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 
 		#[extrinsic_call]
@@ -258,7 +260,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(20u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(20u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -287,7 +291,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -320,7 +326,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get().saturating_mul(2u32.into())),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -352,7 +360,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get().saturating_mul(2u32.into())),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -388,7 +398,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -423,7 +435,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -459,7 +473,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 		T::Currency::set_balance(
 			&Broker::<T>::account_id(),
@@ -486,7 +502,7 @@ mod benches {
 		);
 
 		#[extrinsic_call]
-		_(RawOrigin::Signed(caller), region, m);
+		_(RawOrigin::Signed(caller), region, m, None);
 
 		assert!(InstaPoolHistory::<T>::get(region.begin).is_none());
 		assert_last_event::<T>(
@@ -514,7 +530,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(30u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(30u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 		T::Currency::set_balance(&Broker::<T>::account_id(), T::Currency::minimum_balance());
 
@@ -547,7 +565,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -578,7 +598,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(10u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 
 		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
@@ -700,7 +722,9 @@ mod benches {
 		let caller: T::AccountId = whitelisted_caller();
 		T::Currency::set_balance(
 			&caller.clone(),
-			T::Currency::minimum_balance().saturating_add(30u32.into()),
+			T::Currency::minimum_balance()
+				.saturating_add(30u32.into())
+				.saturating_add(T::RegionDeposit::get()),
 		);
 		T::Currency::set_balance(&Broker::<T>::account_id(), T::Currency::minimum_balance());
 
@@ -852,6 +876,710 @@ mod benches {
 		}
 	}
 
+	#[benchmark]
+	fn purchase_guarded() -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), 10u32.into(), 1);
+
+		assert_last_event::<T>(
+			Event::Purchased {
+				who: caller,
+				region_id: RegionId { begin: 4, core, mask: CoreMask::complete() },
+				price: 10u32.into(),
+				duration: 3u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn renew_pooled() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(20u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		Broker::<T>::do_pool(region, None, caller.clone(), Final)
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		advance_to::<T>(6);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region);
+
+		let id = AllowedRenewalId { core: region.core, when: 10 };
+		assert!(AllowedRenewals::<T>::get(id).is_some());
+		assert_last_event::<T>(
+			Event::Pooled {
+				region_id: RegionId { begin: 7, core: region.core, mask: CoreMask::complete() },
+				duration: 3u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn cancel_sale(c: Linear<0, { MAX_CORE_COUNT.into() }>) -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		// Assume a partially sold core for worst case
+		Broker::<T>::do_purchase_part(caller, CoreMask::from_chunk(0, 40), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		// Leave `c` further cores unsold
+		SaleInfo::<T>::mutate(|maybe_sale| {
+			if let Some(sale) = maybe_sale {
+				sale.cores_offered = (c as CoreIndex).saturating_add(1);
+			}
+		});
+
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, c as CoreIndex);
+
+		assert!(CancelledSale::<T>::get().is_some());
+		assert!(PartiallySoldCore::<T>::get().is_none());
+		assert_last_event::<T>(Event::SaleCancelled { cores_pooled: c as CoreIndex }.into());
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn assign_multi() -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		// Max assignments for worst case, each taking its own part of the core
+		let assignments = WeightedAssignments::truncate_from(
+			(0..CORE_MASK_BITS as u32).map(|task| (task, 1)).collect(),
+		);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region, assignments, Final);
+
+		let workplan_key = (region.begin, region.core);
+		assert_eq!(Workplan::<T>::get(workplan_key).map(|w| w.len()), Some(CORE_MASK_BITS));
+		assert_last_event::<T>(
+			Event::Assigned {
+				region_id: RegionId { begin: 4, core, mask: CoreMask::from_chunk(79, 80) },
+				task: 79,
+				duration: 3u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn list_region() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), region, 20u32.into());
+
+		assert_last_event::<T>(
+			Event::RegionListed { region_id: region, seller: caller, price: 20u32.into() }.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn unlist_region() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		Broker::<T>::do_list_region(region, caller.clone(), 20u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region);
+
+		assert!(Listings::<T>::get(region).is_none());
+		assert_last_event::<T>(Event::RegionUnlisted { region_id: region }.into());
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn buy_listed() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		Broker::<T>::do_list_region(region, caller.clone(), 20u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		let buyer: T::AccountId = account("buyer", 0, SEED);
+		T::Currency::set_balance(
+			&buyer.clone(),
+			T::Currency::minimum_balance().saturating_add(20u32.into()),
+		);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(buyer.clone()), region, 20u32.into());
+
+		assert_eq!(Regions::<T>::get(region).map(|r| r.owner), Some(buyer.clone()));
+		assert_last_event::<T>(
+			Event::ListedRegionSold {
+				region_id: region,
+				seller: caller,
+				buyer,
+				price: 20u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn place_order() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		Status::<T>::mutate(|maybe_status| {
+			if let Some(status) = maybe_status {
+				status.system_pool_size = CORE_MASK_BITS as u32;
+			}
+		});
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance().saturating_add(10u32.into()),
+		);
+
+		let price = Broker::<T>::spot_price().map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), 2000, 10u32.into());
+
+		assert_eq!(InstaPoolOrderRevenue::<T>::iter_values().collect::<Vec<_>>(), vec![price]);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_reservation() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		let schedule = new_schedule();
+
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, 0, schedule.clone());
+
+		assert_eq!(Reservations::<T>::get().len(), T::MaxReservedCores::get() as usize);
+		assert_last_event::<T>(Event::ReservationMade { index: 0, workload: schedule }.into());
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn purchase_part() -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let part = CoreMask::from_chunk(0, 40);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), part, 10u32.into());
+
+		assert_eq!(PartiallySoldCore::<T>::get(), Some((core, part)));
+		assert_last_event::<T>(
+			Event::Purchased {
+				who: caller,
+				region_id: RegionId { begin: 4, core, mask: part },
+				price: 5u32.into(),
+				duration: 3u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn interlace_range(n: Linear<1, 16>) -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance().saturating_add(T::RegionDeposit::get() * n.into()),
+		);
+
+		let mut region_ids = Vec::new();
+		for i in 0..n {
+			let region_id = RegionId { begin: 4 + i, core, mask: CoreMask::complete() };
+			let record = RegionRecord { end: 7 + i, owner: caller.clone(), paid: None };
+			Regions::<T>::insert(region_id, record);
+			region_ids.push(region_id);
+		}
+		let region_ids = CoreRegions::truncate_from(region_ids);
+		let pivot: CoreMask = 0x00000_fffff_fffff_00000.into();
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region_ids.clone(), pivot);
+
+		for region_id in region_ids {
+			assert!(Regions::<T>::get(region_id).is_none());
+			assert!(Regions::<T>::get(RegionId { mask: pivot, ..region_id }).is_some());
+		}
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn notify_revenue() -> Result<(), BenchmarkError> {
+		let revenue = RevenueInfo { until: 10u32.into(), amount: 10u32.into() };
+
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, revenue);
+
+		assert!(RevenueInbox::<T>::get().is_some());
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn compound_revenue(
+		m: Linear<1, { new_config_record::<T>().region_length }>,
+	) -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		Broker::<T>::do_pool(region, None, caller.clone(), Final)
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		let revenue = 10u32.into();
+		InstaPoolHistory::<T>::insert(
+			region.begin,
+			InstaPoolHistoryRecord {
+				private_contributions: 80u32.into(),
+				system_contributions: 0u32.into(),
+				maybe_payout: Some(revenue),
+			},
+		);
+
+		let beneficiary: RelayAccountIdOf<T> = account("beneficiary", 0, SEED);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), region, m, beneficiary.clone());
+
+		assert!(InstaPoolHistory::<T>::get(region.begin).is_none());
+		assert_last_event::<T>(
+			Event::RevenueCompounded {
+				who: caller,
+				beneficiary,
+				amount: revenue,
+				next: if m < new_config_record::<T>().region_length {
+					Some(RegionId { begin: 4.saturating_add(m), core, mask: CoreMask::complete() })
+				} else {
+					None
+				},
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn unpool(
+		m: Linear<1, { new_config_record::<T>().region_length - 1 }>,
+	) -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+		T::Currency::set_balance(
+			&Broker::<T>::account_id(),
+			T::Currency::minimum_balance().saturating_add(200u32.into()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		Broker::<T>::do_pool(region, None, caller.clone(), Provisional)
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		// Let all but the last timeslice of the contribution be consumed by the Pool
+		advance_to::<T>(8);
+
+		for when in region.begin..region.begin.saturating_add(m) {
+			InstaPoolHistory::<T>::insert(
+				when,
+				InstaPoolHistoryRecord {
+					private_contributions: 80u32.into(),
+					system_contributions: 0u32.into(),
+					maybe_payout: Some(10u32.into()),
+				},
+			);
+		}
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region, m);
+
+		assert!(
+			Regions::<T>::get(RegionId { begin: 6, core, mask: CoreMask::complete() }).is_some()
+		);
+		assert_last_event::<T>(
+			Event::Unpooled {
+				region_id: RegionId { begin: 6, core, mask: CoreMask::complete() },
+				duration: 1u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn purchase_with_asset() -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let asset = T::AssetPrice::accepted_asset().ok_or(BenchmarkError::Weightless)?;
+		let amount = T::AssetPrice::asset_price(asset.clone(), 10u32.into())
+			.ok_or(BenchmarkError::Weightless)?;
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance().saturating_add(T::RegionDeposit::get()),
+		);
+		<T::Assets as fungibles::Mutate<_>>::mint_into(asset.clone(), &caller, amount)
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), asset, 10u32.into());
+
+		assert_last_event::<T>(
+			Event::Purchased {
+				who: caller,
+				region_id: RegionId { begin: 4, core, mask: CoreMask::complete() },
+				price: 10u32.into(),
+				duration: 3u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn partition_fraction() -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get().saturating_mul(2u32.into())),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region, Perbill::from_percent(50));
+
+		assert_last_event::<T>(
+			Event::Partitioned {
+				old_region_id: RegionId { begin: 4, core, mask: CoreMask::complete() },
+				new_region_ids: (
+					RegionId { begin: 4, core, mask: CoreMask::complete() },
+					RegionId { begin: 5, core, mask: CoreMask::complete() },
+				),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn transfer_credit() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance().saturating_add(20u32.into()),
+		);
+		T::Currency::set_balance(&Broker::<T>::account_id(), T::Currency::minimum_balance());
+
+		let from = T::ConvertAccount::convert(caller.clone());
+		Broker::<T>::do_purchase_credit(caller.clone(), 20u32.into(), from.clone())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		let to: RelayAccountIdOf<T> = account("beneficiary", 0, SEED);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), to.clone(), 20u32.into());
+
+		assert_last_event::<T>(Event::CreditTransferred { from, to, amount: 20u32.into() }.into());
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn purchase_core() -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		// Choosing a core other than the next one to be sold is the worst case
+		let chosen = core.saturating_add(1);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), chosen, 10u32.into());
+
+		assert_eq!(ChosenCores::<T>::get().into_inner(), vec![chosen]);
+		assert_last_event::<T>(
+			Event::Purchased {
+				who: caller,
+				region_id: RegionId { begin: 4, core: chosen, mask: CoreMask::complete() },
+				price: 10u32.into(),
+				duration: 3u32.into(),
+			}
+			.into(),
+		);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_region_metadata() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		// Max length metadata for worst case
+		let metadata =
+			RegionMetadataOf::<T>::truncate_from(vec![0u8; T::MaxMetadataLen::get() as usize]);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region, metadata.clone());
+
+		assert_last_event::<T>(Event::RegionMetadataSet { region_id: region, metadata }.into());
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn clear_region_metadata() -> Result<(), BenchmarkError> {
+		setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		let metadata =
+			RegionMetadataOf::<T>::truncate_from(vec![0u8; T::MaxMetadataLen::get() as usize]);
+		Broker::<T>::do_set_region_metadata(region, None, metadata)
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region);
+
+		assert!(RegionMetadata::<T>::get(region).is_none());
+		assert_last_event::<T>(Event::RegionMetadataCleared { region_id: region }.into());
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn claim_refund() -> Result<(), BenchmarkError> {
+		let core = setup_and_start_sale::<T>()?;
+
+		advance_to::<T>(2);
+
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::set_balance(
+			&caller.clone(),
+			T::Currency::minimum_balance()
+				.saturating_add(10u32.into())
+				.saturating_add(T::RegionDeposit::get()),
+		);
+
+		let region = Broker::<T>::do_purchase(caller.clone(), 10u32.into())
+			.map_err(|_| BenchmarkError::Weightless)?;
+
+		// The core of the Region is no longer provided by the Relay-chain
+		Status::<T>::mutate(|maybe_status| {
+			if let Some(status) = maybe_status {
+				status.core_count = core;
+			}
+		});
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), region);
+
+		assert!(Regions::<T>::get(region).is_none());
+
+		Ok(())
+	}
+
 	// Implements a test for each benchmark. Execute with:
 	// `cargo test -p pallet-broker --features runtime-benchmarks`.
 	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
@@ -19,6 +19,7 @@ use codec::{Decode, Encode, MaxEncodedLen};
 use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
 use scale_info::TypeInfo;
 use sp_core::RuntimeDebug;
+use sp_std::vec::Vec;
 
 /// The number of bits in the `CoreMask`.
 pub const CORE_MASK_BITS: usize = 80;
@@ -66,6 +67,36 @@ impl CoreMask {
 		}
 		Self(v)
 	}
+	/// The mask with exactly the bits `bits` set, which need not be contiguous. Bits beyond the
+	/// end of the core are ignored.
+	pub fn from_mask(bits: impl IntoIterator<Item = u32>) -> Self {
+		bits.into_iter().fold(Self::void(), |mut a, i| a.set(i))
+	}
+	/// The contiguous ranges of set bits `(from, to)`, in the same form as taken by `from_chunk`.
+	pub fn to_ranges(&self) -> Vec<(u32, u32)> {
+		let mut ranges: Vec<(u32, u32)> = Vec::new();
+		for i in 0..80u32 {
+			if self.0[(i / 8) as usize] & (128 >> (i % 8)) == 0 {
+				continue
+			}
+			match ranges.last_mut() {
+				Some(last) if last.1 == i => last.1 = i + 1,
+				_ => ranges.push((i, i + 1)),
+			}
+		}
+		ranges
+	}
+}
+impl core::fmt::Display for CoreMask {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		for (i, (from, to)) in self.to_ranges().into_iter().enumerate() {
+			if i > 0 {
+				f.write_str(",")?;
+			}
+			write!(f, "{}..{}", from, to)?;
+		}
+		Ok(())
+	}
 }
 impl From<u128> for CoreMask {
 	fn from(x: u128) -> Self {
@@ -180,6 +211,37 @@ mod tests {
 		assert_eq!(CoreMask::from_chunk(40, 60), CoreMask::from(0x00000_00000_fffff_00000),);
 	}
 
+	#[test]
+	fn from_mask_works() {
+		assert_eq!(CoreMask::from_mask([]), CoreMask::void());
+		assert_eq!(CoreMask::from_mask(0..80), CoreMask::complete());
+		assert_eq!(CoreMask::from_mask(40..60), CoreMask::from_chunk(40, 60));
+		let striped = CoreMask::from_mask((0..80).step_by(2));
+		assert_eq!(striped.count_ones(), 40);
+		assert_eq!(u128::from(striped), 0xaaaaa_aaaaa_aaaaa_aaaaa);
+		assert_eq!(CoreMask::from_mask([3, 79, 80, 200]), CoreMask::from_mask([3, 79]));
+	}
+
+	#[test]
+	fn to_ranges_works() {
+		assert_eq!(CoreMask::void().to_ranges(), vec![]);
+		assert_eq!(CoreMask::complete().to_ranges(), vec![(0, 80)]);
+		for (from, to) in [(0, 1), (0, 40), (40, 60), (79, 80), (13, 71)] {
+			assert_eq!(CoreMask::from_chunk(from, to).to_ranges(), vec![(from, to)]);
+		}
+		let mask = CoreMask::from_chunk(0, 20) | CoreMask::from_chunk(40, 60);
+		assert_eq!(mask.to_ranges(), vec![(0, 20), (40, 60)]);
+		assert_eq!(CoreMask::from(0x00000_00000_fffff_00000).to_ranges(), vec![(40, 60)]);
+	}
+
+	#[test]
+	fn display_works() {
+		assert_eq!(CoreMask::void().to_string(), "");
+		assert_eq!(CoreMask::complete().to_string(), "0..80");
+		let mask = CoreMask::from_chunk(0, 40) | CoreMask::from_chunk(60, 61);
+		assert_eq!(mask.to_string(), "0..40,60..61");
+	}
+
 	#[test]
 	fn bit_or_works() {
 		assert_eq!(
@@ -52,10 +52,10 @@ pub enum CoreAssignment {
 /// The trait representation of RFC#5 `<https://github.com/polkadot-fellows/RFCs/pull/5>`.
 pub trait CoretimeInterface {
 	/// A (Relay-chain-side) account ID.
-	type AccountId: Parameter;
+	type AccountId: Parameter + MaxEncodedLen;
 
 	/// A (Relay-chain-side) balance.
-	type Balance: AtLeast32BitUnsigned;
+	type Balance: AtLeast32BitUnsigned + Copy + TypeInfo + Encode + Decode + MaxEncodedLen + Debug;
 
 	/// A (Relay-chain-side) block number.
 	type BlockNumber: AtLeast32BitUnsigned
@@ -91,6 +91,15 @@ pub trait CoretimeInterface {
 	/// Pool.
 	fn credit_account(who: Self::AccountId, amount: Self::Balance);
 
+	/// Instructs the Relay-chain to remove up to `amount` of DOT of unspent Instantaneous Coretime
+	/// Market Credit from the account of `who`. Returns the amount actually removed.
+	fn revoke_credit(who: Self::AccountId, amount: Self::Balance) -> Self::Balance;
+
+	/// Instructs the Relay-chain to schedule a single block of Instantaneous Coretime for `task`
+	/// as soon after `begin` as possible. The order has already been paid for with `amount` of
+	/// DOT, so it must not be charged to any Instantaneous Coretime Market Credit.
+	fn place_order(task: TaskId, begin: Self::BlockNumber, amount: Self::Balance);
+
 	/// Instructs the Relay-chain to ensure that the core indexed as `core` is utilised for a number
 	/// of assignments in specific ratios given by `assignment` starting as soon after `begin` as
 	/// possible. Core assignments take the form of a `CoreAssignment` value which can either task
@@ -148,6 +157,10 @@ impl CoretimeInterface for () {
 	fn request_core_count(_count: CoreIndex) {}
 	fn request_revenue_info_at(_when: Self::BlockNumber) {}
 	fn credit_account(_who: Self::AccountId, _amount: Self::Balance) {}
+	fn revoke_credit(_who: Self::AccountId, _amount: Self::Balance) -> Self::Balance {
+		0
+	}
+	fn place_order(_task: TaskId, _begin: Self::BlockNumber, _amount: Self::Balance) {}
 	fn assign_core(
 		_core: CoreIndex,
 		_begin: Self::BlockNumber,
@@ -20,9 +20,13 @@ use frame_support::{
 	pallet_prelude::{DispatchResult, *},
 	traits::{fungible::Mutate, tokens::Preservation::Expendable, DefensiveResult},
 };
-use sp_arithmetic::traits::{CheckedDiv, Saturating, Zero};
+use sp_arithmetic::{
+	traits::{CheckedAdd, CheckedDiv, One, SaturatedConversion, Saturating, Zero},
+	Perbill,
+};
 use sp_runtime::traits::Convert;
-use CompletionStatus::{Complete, Partial};
+use sp_std::{vec, vec::Vec};
+use CompletionStatus::Complete;
 
 impl<T: Config> Pallet<T> {
 	pub(crate) fn do_configure(config: ConfigRecordOf<T>) -> DispatchResult {
@@ -37,10 +41,19 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	pub(crate) fn do_notify_revenue(revenue: RevenueInfoOf<T>) -> DispatchResult {
+		ensure!(!RevenueInbox::<T>::exists(), Error::<T>::RevenuePending);
+		RevenueInbox::<T>::put(revenue);
+		Ok(())
+	}
+
 	pub(crate) fn do_reserve(workload: Schedule) -> DispatchResult {
+		ensure!(Self::is_disjoint(&workload), Error::<T>::OverlappingReservation);
+		ensure!(Self::is_complete(&workload), Error::<T>::IncompleteSchedule);
 		let mut r = Reservations::<T>::get();
 		let index = r.len() as u32;
 		r.try_push(workload.clone()).map_err(|_| Error::<T>::TooManyReservations)?;
+		T::OnReserve::on_reserve(index, &workload)?;
 		Reservations::<T>::put(r);
 		Self::deposit_event(Event::<T>::ReservationMade { index, workload });
 		Ok(())
@@ -55,6 +68,28 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	pub(crate) fn do_set_reservation(index: u32, workload: Schedule) -> DispatchResult {
+		ensure!(Self::is_disjoint(&workload), Error::<T>::OverlappingReservation);
+		ensure!(Self::is_complete(&workload), Error::<T>::IncompleteSchedule);
+		let mut r = Reservations::<T>::get();
+		match r.get_mut(index as usize) {
+			Some(reservation) => *reservation = workload.clone(),
+			None if index == r.len() as u32 => {
+				r.try_push(workload.clone()).map_err(|_| Error::<T>::TooManyReservations)?;
+			},
+			None => return Err(Error::<T>::UnknownReservation.into()),
+		}
+		// Reservations and leases must leave no fewer than zero cores for sale.
+		if let Some(status) = Status::<T>::get() {
+			let reserved = r.len().saturating_add(Leases::<T>::decode_len().unwrap_or(0));
+			ensure!(reserved <= status.core_count as usize, Error::<T>::TooManyReservations);
+		}
+		T::OnReserve::on_reserve(index, &workload)?;
+		Reservations::<T>::put(r);
+		Self::deposit_event(Event::<T>::ReservationMade { index, workload });
+		Ok(())
+	}
+
 	pub(crate) fn do_set_lease(task: TaskId, until: Timeslice) -> DispatchResult {
 		let mut r = Leases::<T>::get();
 		ensure!(until > Self::current_timeslice(), Error::<T>::AlreadyExpired);
@@ -65,9 +100,19 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
-	pub(crate) fn do_start_sales(price: BalanceOf<T>, core_count: CoreIndex) -> DispatchResult {
+	/// Begin the sales rotation, with the Regions of the first sale beginning at timeslice
+	/// `first_cycle_start` or, if `None`, one Region's length after the latest timeslice which is
+	/// ready to be committed.
+	pub(crate) fn do_start_sales(
+		price: BalanceOf<T>,
+		core_count: CoreIndex,
+		first_cycle_start: Option<Timeslice>,
+	) -> DispatchResult {
 		let config = Configuration::<T>::get().ok_or(Error::<T>::Uninitialized)?;
 		let commit_timeslice = Self::latest_timeslice_ready_to_commit(&config);
+		let first_cycle_start =
+			first_cycle_start.unwrap_or(commit_timeslice.saturating_add(config.region_length));
+		ensure!(first_cycle_start > commit_timeslice, Error::<T>::FirstCycleTooEarly);
 		let status = StatusRecord {
 			core_count,
 			private_pool_size: 0,
@@ -81,8 +126,8 @@ impl<T: Config> Pallet<T> {
 			leadin_length: Zero::zero(),
 			price,
 			sellout_price: None,
-			region_begin: commit_timeslice,
-			region_end: commit_timeslice.saturating_add(config.region_length),
+			region_begin: first_cycle_start.saturating_sub(config.region_length),
+			region_end: first_cycle_start,
 			first_core: 0,
 			ideal_cores_sold: 0,
 			cores_offered: 0,
@@ -94,21 +139,67 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Purchase a core in the ongoing sale. The core purchased is always the lowest-indexed one
+	/// of the sale which has not yet been sold, renewed or chosen by `do_purchase_core`.
 	pub(crate) fn do_purchase(
 		who: T::AccountId,
 		price_limit: BalanceOf<T>,
+	) -> Result<RegionId, DispatchError> {
+		Self::do_purchase_with(who, price_limit, None)
+	}
+
+	/// Purchase a core in the ongoing sale, paying in `maybe_asset` rather than the native
+	/// currency if given. `price_limit` is denominated in the native currency either way.
+	pub(crate) fn do_purchase_with(
+		who: T::AccountId,
+		price_limit: BalanceOf<T>,
+		maybe_asset: Option<AssetIdOf<T>>,
+	) -> Result<RegionId, DispatchError> {
+		Self::purchase(who, price_limit, maybe_asset, None)
+	}
+
+	/// Purchase the specific `core` in the ongoing sale. Fails with `CoreUnavailable` if the core
+	/// is not offered in the sale or has already been sold.
+	pub(crate) fn do_purchase_core(
+		who: T::AccountId,
+		core: CoreIndex,
+		price_limit: BalanceOf<T>,
+	) -> Result<RegionId, DispatchError> {
+		Self::purchase(who, price_limit, None, Some(core))
+	}
+
+	fn purchase(
+		who: T::AccountId,
+		price_limit: BalanceOf<T>,
+		maybe_asset: Option<AssetIdOf<T>>,
+		maybe_core: Option<CoreIndex>,
 	) -> Result<RegionId, DispatchError> {
 		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
 		let mut sale = SaleInfo::<T>::get().ok_or(Error::<T>::NoSales)?;
 		ensure!(sale.first_core < status.core_count, Error::<T>::Unavailable);
 		ensure!(sale.cores_sold < sale.cores_offered, Error::<T>::SoldOut);
+		ensure!(!CancelledSale::<T>::exists(), Error::<T>::SoldOut);
 		let now = frame_system::Pallet::<T>::block_number();
 		ensure!(now > sale.sale_start, Error::<T>::TooEarly);
 		let price = Self::sale_price(&sale, now);
 		ensure!(price_limit >= price, Error::<T>::Overpriced);
 
-		Self::charge(&who, price)?;
-		let core = sale.first_core.saturating_add(sale.cores_sold);
+		let mut chosen = ChosenCores::<T>::get();
+		let next_free = Self::next_free_core(&sale, &chosen);
+		let core = match maybe_core {
+			Some(core) if core != next_free => {
+				let last_offered = sale.first_core.saturating_add(sale.cores_offered);
+				ensure!(core > next_free && core < last_offered, Error::<T>::CoreUnavailable);
+				let index = chosen.binary_search(&core).err().ok_or(Error::<T>::CoreUnavailable)?;
+				chosen.try_insert(index, core).map_err(|_| Error::<T>::CoreUnavailable)?;
+				core
+			},
+			_ => next_free,
+		};
+		let mask = CoreMask::complete();
+		Self::take_region_deposit(&who, &RegionId { begin: sale.region_begin, core, mask })?;
+		Self::charge_with(&who, price, maybe_asset)?;
+		ChosenCores::<T>::put(&chosen);
 		sale.cores_sold.saturating_inc();
 		if sale.cores_sold <= sale.ideal_cores_sold || sale.sellout_price.is_none() {
 			sale.sellout_price = Some(price);
@@ -120,9 +211,119 @@ impl<T: Config> Pallet<T> {
 		Ok(id)
 	}
 
+	/// Purchase `part` of a core in the ongoing sale at the proportional price. The first part
+	/// purchased of a core counts it as sold; its remaining parts stay available through this
+	/// function until the sale is rotated, when any unsold parts are placed into the pool.
+	pub(crate) fn do_purchase_part(
+		who: T::AccountId,
+		part: CoreMask,
+		price_limit: BalanceOf<T>,
+	) -> Result<RegionId, DispatchError> {
+		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
+		let mut sale = SaleInfo::<T>::get().ok_or(Error::<T>::NoSales)?;
+		ensure!(!part.is_void(), Error::<T>::VoidPart);
+		ensure!(!CancelledSale::<T>::exists(), Error::<T>::SoldOut);
+		let (core, sold) = match PartiallySoldCore::<T>::get() {
+			Some((core, sold)) => {
+				ensure!((sold & part).is_void(), Error::<T>::PartUnavailable);
+				(core, sold)
+			},
+			None => {
+				ensure!(sale.first_core < status.core_count, Error::<T>::Unavailable);
+				ensure!(sale.cores_sold < sale.cores_offered, Error::<T>::SoldOut);
+				(Self::next_free_core(&sale, &ChosenCores::<T>::get()), CoreMask::void())
+			},
+		};
+		let now = frame_system::Pallet::<T>::block_number();
+		ensure!(now > sale.sale_start, Error::<T>::TooEarly);
+		let price = Self::sale_price(&sale, now).saturating_mul(part.count_ones().into()) /
+			(CORE_MASK_BITS as u32).into();
+		ensure!(price_limit >= price, Error::<T>::Overpriced);
+
+		let id = RegionId { begin: sale.region_begin, core, mask: part };
+		Self::take_region_deposit(&who, &id)?;
+		Self::charge(&who, price)?;
+		if sold.is_void() {
+			sale.cores_sold.saturating_inc();
+			SaleInfo::<T>::put(&sale);
+		}
+		let sold = sold | part;
+		if sold.is_complete() {
+			PartiallySoldCore::<T>::kill();
+		} else {
+			PartiallySoldCore::<T>::put((core, sold));
+		}
+		let record = RegionRecord { end: sale.region_end, owner: who.clone(), paid: Some(price) };
+		Regions::<T>::insert(&id, &record);
+		let duration = sale.region_end.saturating_sub(sale.region_begin);
+		Self::deposit_event(Event::Purchased { who, region_id: id, price, duration });
+		Ok(id)
+	}
+
+	/// Like `do_purchase`, but fails with `CoresExhausted` if fewer than `min_cores_remaining`
+	/// cores (including the one to be purchased) are still on sale, e.g. due to being front-run.
+	pub(crate) fn do_purchase_guarded(
+		who: T::AccountId,
+		price_limit: BalanceOf<T>,
+		min_cores_remaining: CoreIndex,
+	) -> Result<RegionId, DispatchError> {
+		let sale = SaleInfo::<T>::get().ok_or(Error::<T>::NoSales)?;
+		let remaining = sale.cores_offered.saturating_sub(sale.cores_sold);
+		ensure!(remaining >= min_cores_remaining, Error::<T>::CoresExhausted);
+		Self::do_purchase(who, price_limit)
+	}
+
+	pub(crate) fn do_cancel_sale(max_cores: CoreIndex) -> Result<CoreIndex, DispatchError> {
+		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
+		let sale = SaleInfo::<T>::get().ok_or(Error::<T>::NoSales)?;
+		ensure!(!CancelledSale::<T>::exists(), Error::<T>::SoldOut);
+		let cores_pooled = sale.cores_offered.saturating_sub(sale.cores_sold);
+		ensure!(cores_pooled <= max_cores, Error::<T>::TooManyUnsoldCores);
+
+		// Regions which have already begun can only be pooled from the next uncommitted timeslice.
+		let begin = sale.region_begin.max(status.last_committed_timeslice.saturating_add(1));
+		if begin < sale.region_end {
+			let pool_item =
+				ScheduleItem { assignment: CoreAssignment::Pool, mask: CoreMask::complete() };
+			let just_pool = Schedule::truncate_from(vec![pool_item]);
+			let mut pooled: SignedCoreMaskBitCount = 0;
+			for core in Self::unsold_cores(&sale, &ChosenCores::<T>::get()) {
+				pooled.saturating_accrue(80);
+				Workplan::<T>::insert((begin, core), &just_pool);
+			}
+			if let Some((core, sold)) = PartiallySoldCore::<T>::take() {
+				let unsold = !sold;
+				pooled.saturating_accrue(unsold.count_ones() as SignedCoreMaskBitCount);
+				let pool_item = ScheduleItem { assignment: CoreAssignment::Pool, mask: unsold };
+				Workplan::<T>::mutate((begin, core), |p| {
+					let _ = p.get_or_insert_with(Default::default).try_push(pool_item);
+				});
+			}
+			InstaPoolIo::<T>::mutate(begin, |r| r.system.saturating_accrue(pooled));
+			InstaPoolIo::<T>::mutate(sale.region_end, |r| r.system.saturating_reduce(pooled));
+		}
+
+		// Nothing more may be purchased and the sale's rotation will not pool the cores again.
+		// The sale record is left alone so that renewals can still take the pooled cores.
+		PartiallySoldCore::<T>::kill();
+		CancelledSale::<T>::put(begin);
+		Self::deposit_event(Event::SaleCancelled { cores_pooled });
+		Ok(cores_pooled)
+	}
+
 	/// Must be called on a core in `AllowedRenewals` whose value is a timeslice equal to the
-	/// current sale status's `region_end`.
+	/// current sale status's `region_end`. A core whose workload includes the Instantaneous
+	/// Coretime Pool may only be renewed through `do_renew_pooled`, which accounts for the
+	/// contribution.
 	pub(crate) fn do_renew(who: T::AccountId, core: CoreIndex) -> Result<CoreIndex, DispatchError> {
+		Self::renew(who, core, false)
+	}
+
+	fn renew(
+		who: T::AccountId,
+		core: CoreIndex,
+		allow_pool: bool,
+	) -> Result<CoreIndex, DispatchError> {
 		let config = Configuration::<T>::get().ok_or(Error::<T>::Uninitialized)?;
 		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
 		let mut sale = SaleInfo::<T>::get().ok_or(Error::<T>::NoSales)?;
@@ -133,9 +334,13 @@ impl<T: Config> Pallet<T> {
 		let record = AllowedRenewals::<T>::get(renewal_id).ok_or(Error::<T>::NotAllowed)?;
 		let workload =
 			record.completion.drain_complete().ok_or(Error::<T>::IncompleteAssignment)?;
+		ensure!(
+			allow_pool || workload.iter().all(|i| i.assignment != CoreAssignment::Pool),
+			Error::<T>::NotAllowed
+		);
 
 		let old_core = core;
-		let core = sale.first_core.saturating_add(sale.cores_sold);
+		let core = Self::next_free_core(&sale, &ChosenCores::<T>::get());
 		Self::charge(&who, record.price)?;
 		Self::deposit_event(Event::Renewed {
 			who,
@@ -149,6 +354,14 @@ impl<T: Config> Pallet<T> {
 
 		sale.cores_sold.saturating_inc();
 
+		// The core was placed into the pool when the sale was cancelled; take it back out.
+		if let Some(pooled_from) = CancelledSale::<T>::get() {
+			if pooled_from < sale.region_end {
+				Workplan::<T>::remove((pooled_from, core));
+				InstaPoolIo::<T>::mutate(pooled_from, |r| r.system.saturating_reduce(80));
+				InstaPoolIo::<T>::mutate(sale.region_end, |r| r.system.saturating_accrue(80));
+			}
+		}
 		Workplan::<T>::insert((sale.region_begin, core), &workload);
 
 		let begin = sale.region_end;
@@ -179,6 +392,8 @@ impl<T: Config> Pallet<T> {
 		let old_owner = region.owner;
 		region.owner = new_owner;
 		Regions::<T>::insert(&region_id, &region);
+		Listings::<T>::remove(&region_id);
+		RegionMetadata::<T>::remove(&region_id);
 		let duration = region.end.saturating_sub(region_id.begin);
 		Self::deposit_event(Event::Transferred {
 			region_id,
@@ -190,11 +405,81 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	pub(crate) fn do_set_region_metadata(
+		region_id: RegionId,
+		maybe_check_owner: Option<T::AccountId>,
+		metadata: RegionMetadataOf<T>,
+	) -> Result<(), Error<T>> {
+		let region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
+		if let Some(check_owner) = maybe_check_owner {
+			ensure!(check_owner == region.owner, Error::<T>::NotOwner);
+		}
+
+		RegionMetadata::<T>::insert(&region_id, &metadata);
+		Self::deposit_event(Event::RegionMetadataSet { region_id, metadata });
+		Ok(())
+	}
+
+	pub(crate) fn do_clear_region_metadata(
+		region_id: RegionId,
+		maybe_check_owner: Option<T::AccountId>,
+	) -> Result<(), Error<T>> {
+		let region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
+		if let Some(check_owner) = maybe_check_owner {
+			ensure!(check_owner == region.owner, Error::<T>::NotOwner);
+		}
+
+		RegionMetadata::<T>::remove(&region_id);
+		Self::deposit_event(Event::RegionMetadataCleared { region_id });
+		Ok(())
+	}
+
+	pub(crate) fn do_list_region(
+		region_id: RegionId,
+		seller: T::AccountId,
+		price: BalanceOf<T>,
+	) -> Result<(), Error<T>> {
+		let region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
+		ensure!(seller == region.owner, Error::<T>::NotOwner);
+
+		Listings::<T>::insert(&region_id, (&seller, price));
+		Self::deposit_event(Event::RegionListed { region_id, seller, price });
+		Ok(())
+	}
+
+	pub(crate) fn do_unlist_region(
+		region_id: RegionId,
+		maybe_check_seller: Option<T::AccountId>,
+	) -> Result<(), Error<T>> {
+		let (seller, _) = Listings::<T>::get(&region_id).ok_or(Error::<T>::NotListed)?;
+		if let Some(check_seller) = maybe_check_seller {
+			ensure!(check_seller == seller, Error::<T>::NotOwner);
+		}
+
+		Listings::<T>::remove(&region_id);
+		Self::deposit_event(Event::RegionUnlisted { region_id });
+		Ok(())
+	}
+
+	pub(crate) fn do_buy_listed(
+		buyer: T::AccountId,
+		region_id: RegionId,
+		price_limit: BalanceOf<T>,
+	) -> DispatchResult {
+		let (seller, price) = Listings::<T>::get(&region_id).ok_or(Error::<T>::NotListed)?;
+		ensure!(price_limit >= price, Error::<T>::Overpriced);
+
+		T::Currency::transfer(&buyer, &seller, price, Expendable)?;
+		Self::do_transfer(region_id, Some(seller.clone()), buyer.clone())?;
+		Self::deposit_event(Event::ListedRegionSold { region_id, seller, buyer, price });
+		Ok(())
+	}
+
 	pub(crate) fn do_partition(
 		region_id: RegionId,
 		maybe_check_owner: Option<T::AccountId>,
 		pivot_offset: Timeslice,
-	) -> Result<(RegionId, RegionId), Error<T>> {
+	) -> Result<(RegionId, RegionId), DispatchError> {
 		let mut region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
 
 		if let Some(check_owner) = maybe_check_owner {
@@ -204,21 +489,48 @@ impl<T: Config> Pallet<T> {
 		ensure!(pivot < region.end, Error::<T>::PivotTooLate);
 		ensure!(pivot > region_id.begin, Error::<T>::PivotTooEarly);
 
-		region.paid = None;
+		// Split the amount paid in proportion to the timeslices on either side of the pivot. Both
+		// sides last less than a sale period, so neither can be renewed.
+		let paid_before = region.paid.map(|paid| {
+			paid.saturating_mul(pivot.saturating_sub(region_id.begin).into())
+				.checked_div(&region.end.saturating_sub(region_id.begin).into())
+				.unwrap_or_default()
+		});
+		let before = RegionRecord { end: pivot, paid: paid_before, ..region.clone() };
+		region.paid = region.paid.zip(paid_before).map(|(paid, p)| paid.saturating_sub(p));
 		let new_region_ids = (region_id, RegionId { begin: pivot, ..region_id });
+		Self::take_region_deposit(&region.owner, &new_region_ids.1)?;
+		Listings::<T>::remove(&region_id);
+		RegionMetadata::<T>::remove(&region_id);
 
-		Regions::<T>::insert(&new_region_ids.0, &RegionRecord { end: pivot, ..region.clone() });
+		Regions::<T>::insert(&new_region_ids.0, &before);
 		Regions::<T>::insert(&new_region_ids.1, &region);
 		Self::deposit_event(Event::Partitioned { old_region_id: region_id, new_region_ids });
 
 		Ok(new_region_ids)
 	}
 
+	/// Partition `region_id` at `fraction` of its length, rounded to the nearest timeslice with
+	/// halves rounded down. A fraction leaving either side without any timeslice is rejected as
+	/// `do_partition` would.
+	pub(crate) fn do_partition_fraction(
+		region_id: RegionId,
+		maybe_check_owner: Option<T::AccountId>,
+		fraction: Perbill,
+	) -> Result<(RegionId, RegionId), DispatchError> {
+		let region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
+		let length = region.end.saturating_sub(region_id.begin);
+		let pivot_offset = fraction * length;
+		ensure!(pivot_offset > 0, Error::<T>::PivotTooEarly);
+		ensure!(pivot_offset < length, Error::<T>::PivotTooLate);
+		Self::do_partition(region_id, maybe_check_owner, pivot_offset)
+	}
+
 	pub(crate) fn do_interlace(
 		region_id: RegionId,
 		maybe_check_owner: Option<T::AccountId>,
 		pivot: CoreMask,
-	) -> Result<(RegionId, RegionId), Error<T>> {
+	) -> Result<(RegionId, RegionId), DispatchError> {
 		let region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
 
 		if let Some(check_owner) = maybe_check_owner {
@@ -230,8 +542,15 @@ impl<T: Config> Pallet<T> {
 		ensure!(pivot != region_id.mask, Error::<T>::CompletePivot);
 
 		let one = RegionId { mask: pivot, ..region_id };
-		Regions::<T>::insert(&one, &region);
 		let other = RegionId { mask: region_id.mask ^ pivot, ..region_id };
+		Self::take_region_deposit(&region.owner, &other)?;
+		Listings::<T>::remove(&region_id);
+		RegionMetadata::<T>::remove(&region_id);
+		Regions::<T>::remove(&region_id);
+		if let Some(deposit) = RegionDeposits::<T>::take(&region_id) {
+			RegionDeposits::<T>::insert(&one, deposit);
+		}
+		Regions::<T>::insert(&one, &region);
 		Regions::<T>::insert(&other, &region);
 
 		let new_region_ids = (one, other);
@@ -239,6 +558,30 @@ impl<T: Config> Pallet<T> {
 		Ok(new_region_ids)
 	}
 
+	/// Interlace each of `region_ids` along `pivot`, returning the pairs of new Regions in the
+	/// same order. Either all of the Regions are interlaced or none are.
+	pub(crate) fn do_interlace_range(
+		region_ids: CoreRegions,
+		maybe_check_owner: Option<T::AccountId>,
+		pivot: CoreMask,
+	) -> Result<Vec<(RegionId, RegionId)>, DispatchError> {
+		ensure!(!pivot.is_void(), Error::<T>::VoidPivot);
+		let core = region_ids.first().map(|r| r.core);
+		for region_id in region_ids.iter() {
+			let region = Regions::<T>::get(region_id).ok_or(Error::<T>::UnknownRegion)?;
+			if let Some(check_owner) = &maybe_check_owner {
+				ensure!(check_owner == &region.owner, Error::<T>::NotOwner);
+			}
+			ensure!(Some(region_id.core) == core, Error::<T>::DifferentCores);
+			ensure!((pivot & !region_id.mask).is_void(), Error::<T>::ExteriorPivot);
+			ensure!(pivot != region_id.mask, Error::<T>::CompletePivot);
+		}
+		region_ids
+			.into_iter()
+			.map(|region_id| Self::do_interlace(region_id, maybe_check_owner.clone(), pivot))
+			.collect()
+	}
+
 	pub(crate) fn do_assign(
 		region_id: RegionId,
 		maybe_check_owner: Option<T::AccountId>,
@@ -261,44 +604,82 @@ impl<T: Config> Pallet<T> {
 				Workplan::<T>::insert(&workplan_key, &workplan);
 			}
 
-			let duration = region.end.saturating_sub(region_id.begin);
-			if duration == config.region_length && finality == Finality::Final {
-				if let Some(price) = region.paid {
-					let renewal_id = AllowedRenewalId { core: region_id.core, when: region.end };
-					let assigned = match AllowedRenewals::<T>::get(renewal_id) {
-						Some(AllowedRenewalRecord { completion: Partial(w), price: p })
-							if price == p =>
-							w,
-						_ => CoreMask::void(),
-					} | region_id.mask;
-					let workload =
-						if assigned.is_complete() { Complete(workplan) } else { Partial(assigned) };
-					let record = AllowedRenewalRecord { price, completion: workload };
-					AllowedRenewals::<T>::insert(&renewal_id, &record);
-					if let Some(workload) = record.completion.drain_complete() {
-						Self::deposit_event(Event::Renewable {
-							core: region_id.core,
-							price,
-							begin: region.end,
-							workload,
-						});
-					}
-				}
+			if finality == Finality::Final {
+				Self::note_renewable(&config, region_id, &region, workplan);
 			}
+
+			let duration = region.end.saturating_sub(region_id.begin);
 			Self::deposit_event(Event::Assigned { region_id, task: target, duration });
 		}
 		Ok(())
 	}
 
+	pub(crate) fn do_assign_multi(
+		region_id: RegionId,
+		maybe_check_owner: Option<T::AccountId>,
+		assignments: WeightedAssignments,
+		finality: Finality,
+	) -> Result<(), Error<T>> {
+		let config = Configuration::<T>::get().ok_or(Error::<T>::Uninitialized)?;
+		let total_weight: u32 = assignments.iter().map(|&(_, weight)| weight as u32).sum();
+		ensure!(total_weight > 0, Error::<T>::ZeroAssignmentWeight);
+
+		// Share out the parts of the Region in order, each task taking its weighted proportion.
+		let parts = (0..CORE_MASK_BITS as u32)
+			.filter(|&i| !(region_id.mask & CoreMask::void().set(i)).is_void())
+			.collect::<Vec<_>>();
+		let mut items = Vec::with_capacity(assignments.len());
+		let (mut taken, mut accumulated_weight) = (0, 0);
+		for (task, weight) in assignments.into_iter() {
+			accumulated_weight += weight as u32;
+			let until = (parts.len() as u32 * accumulated_weight / total_weight) as usize;
+			let mask = parts[taken..until].iter().fold(CoreMask::void(), |mut m, &i| m.set(i));
+			taken = until;
+			if !mask.is_void() {
+				items.push(ScheduleItem { mask, assignment: CoreAssignment::Task(task) });
+			}
+		}
+
+		if let Some((region_id, region)) = Self::utilize(region_id, maybe_check_owner, finality)? {
+			let workplan_key = (region_id.begin, region_id.core);
+			let mut workplan = Workplan::<T>::get(&workplan_key).unwrap_or_default();
+			// Ensure no previous allocations exist.
+			workplan.retain(|i| (i.mask & region_id.mask).is_void());
+			if items.iter().all(|item| workplan.try_push(item.clone()).is_ok()) {
+				Workplan::<T>::insert(&workplan_key, &workplan);
+			}
+
+			if finality == Finality::Final {
+				Self::note_renewable(&config, region_id, &region, workplan);
+			}
+
+			let duration = region.end.saturating_sub(region_id.begin);
+			for item in items.into_iter() {
+				let CoreAssignment::Task(task) = item.assignment else { continue };
+				let region_id = RegionId { mask: item.mask, ..region_id };
+				Self::deposit_event(Event::Assigned { region_id, task, duration });
+			}
+		}
+		Ok(())
+	}
+
 	pub(crate) fn do_pool(
 		region_id: RegionId,
 		maybe_check_owner: Option<T::AccountId>,
 		payee: T::AccountId,
 		finality: Finality,
 	) -> Result<(), Error<T>> {
+		let config = Configuration::<T>::get().ok_or(Error::<T>::Uninitialized)?;
 		if let Some((region_id, region)) = Self::utilize(region_id, maybe_check_owner, finality)? {
 			let workplan_key = (region_id.begin, region_id.core);
 			let mut workplan = Workplan::<T>::get(&workplan_key).unwrap_or_default();
+			// A provisional assignment of this very Region is superseded.
+			workplan.retain(|i| i.mask != region_id.mask);
+			// Only the complement of any part of the core which is already assigned may be pooled.
+			ensure!(
+				workplan.iter().all(|i| (i.mask & region_id.mask).is_void()),
+				Error::<T>::PartAlreadyAssigned
+			);
 			let duration = region.end.saturating_sub(region_id.begin);
 			if workplan
 				.try_push(ScheduleItem { mask: region_id.mask, assignment: CoreAssignment::Pool })
@@ -310,6 +691,9 @@ impl<T: Config> Pallet<T> {
 				InstaPoolIo::<T>::mutate(region.end, |a| a.private.saturating_reduce(size));
 				let record = ContributionRecord { length: duration, payee };
 				InstaPoolContribution::<T>::insert(&region_id, record);
+				if finality == Finality::Final {
+					Self::note_renewable(&config, region_id, &region, workplan);
+				}
 			}
 
 			Self::deposit_event(Event::Pooled { region_id, duration });
@@ -317,10 +701,156 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Withdraw the part of the contribution `region_id` not yet consumed by the Instantaneous
+	/// Coretime Pool, leaving the Region with its owner to be assigned. Only a Region pooled with
+	/// `Provisional` finality is still held and may be withdrawn; a `Final` contribution is
+	/// irrevocable, which also keeps any renewal it made possible valid. The revenue of the
+	/// consumed part is paid out to the payee first as far as it is known, up to
+	/// `max_timeslices`; any remainder stays claimable through `do_claim_revenue`.
+	pub(crate) fn do_unpool(
+		region_id: RegionId,
+		maybe_check_owner: Option<T::AccountId>,
+		max_timeslices: Timeslice,
+	) -> Result<RegionId, DispatchError> {
+		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
+		let contribution =
+			InstaPoolContribution::<T>::get(&region_id).ok_or(Error::<T>::UnknownContribution)?;
+		let end = region_id.begin.saturating_add(contribution.length);
+		let from = region_id.begin.max(status.last_committed_timeslice.saturating_add(1));
+		ensure!(from < end, Error::<T>::AlreadyExpired);
+		let mut region = Regions::<T>::get(&region_id).ok_or(Error::<T>::FinalContribution)?;
+		if let Some(check_owner) = maybe_check_owner {
+			ensure!(check_owner == region.owner, Error::<T>::NotOwner);
+		}
+		let new_region_id = RegionId { begin: from, ..region_id };
+
+		// Settle the consumed timeslices whose revenue is known.
+		let settled = (region_id.begin..from)
+			.take(max_timeslices as usize)
+			.take_while(|r| {
+				InstaPoolHistory::<T>::get(r).map_or(true, |h| h.maybe_payout.is_some())
+			})
+			.count() as Timeslice;
+		if settled > 0 {
+			Self::do_claim_revenue(region_id, settled, None)?;
+		}
+		// Whatever remains of the contribution covers only the consumed timeslices.
+		let remaining = RegionId { begin: region_id.begin.saturating_add(settled), ..region_id };
+		InstaPoolContribution::<T>::mutate_exists(&remaining, |maybe_contribution| {
+			let length = from.saturating_sub(remaining.begin);
+			match maybe_contribution {
+				Some(contribution) if length > 0 => contribution.length = length,
+				_ => *maybe_contribution = None,
+			}
+		});
+
+		let size = region_id.mask.count_ones() as SignedCoreMaskBitCount;
+		InstaPoolIo::<T>::mutate(from, |a| a.private.saturating_reduce(size));
+		InstaPoolIo::<T>::mutate(end, |a| a.private.saturating_accrue(size));
+
+		let workplan_key = (from, region_id.core);
+		let mut workplan = Workplan::<T>::get(&workplan_key).unwrap_or_default();
+		workplan.retain(|i| (i.mask & region_id.mask).is_void());
+		if from > region_id.begin {
+			// The Pool assignment is already in effect and must be overridden.
+			let idle = ScheduleItem { mask: region_id.mask, assignment: CoreAssignment::Idle };
+			workplan.try_push(idle).map_err(|_| Error::<T>::CorruptWorkplan)?;
+		}
+		if workplan.is_empty() {
+			Workplan::<T>::remove(&workplan_key);
+		} else {
+			Workplan::<T>::insert(&workplan_key, &workplan);
+		}
+
+		// The part of the Region consumed by the Pool is gone, so the amount paid is pro-rated to
+		// what remains as it would be by `do_partition`.
+		region.paid = region.paid.map(|paid| {
+			paid.saturating_mul(end.saturating_sub(from).into())
+				.checked_div(&end.saturating_sub(region_id.begin).into())
+				.unwrap_or_default()
+		});
+		Regions::<T>::remove(&region_id);
+		Regions::<T>::insert(&new_region_id, &region);
+		if let Some(deposit) = RegionDeposits::<T>::take(&region_id) {
+			RegionDeposits::<T>::insert(&new_region_id, deposit);
+		}
+		let duration = end.saturating_sub(from);
+		Self::deposit_event(Event::Unpooled { region_id: new_region_id, duration });
+		Ok(new_region_id)
+	}
+
+	/// Renew the whole-core Region `region_id`, which was pooled with `Final` finality, for the
+	/// next sale and place the renewed Region straight into the Instantaneous Coretime Pool with
+	/// the same payee.
+	pub(crate) fn do_renew_pooled(
+		who: T::AccountId,
+		region_id: RegionId,
+	) -> Result<RegionId, DispatchError> {
+		let sale = SaleInfo::<T>::get().ok_or(Error::<T>::NoSales)?;
+		let contribution =
+			InstaPoolContribution::<T>::get(&region_id).ok_or(Error::<T>::UnknownContribution)?;
+		ensure!(region_id.mask.is_complete(), Error::<T>::NotAllowed);
+		let end = region_id.begin.saturating_add(contribution.length);
+		ensure!(end == sale.region_begin, Error::<T>::WrongTime);
+
+		let core = Self::renew(who, region_id.core, true)?;
+
+		let region_id = RegionId { begin: sale.region_begin, core, mask: CoreMask::complete() };
+		let duration = sale.region_end.saturating_sub(sale.region_begin);
+		let size = region_id.mask.count_ones() as i32;
+		InstaPoolIo::<T>::mutate(region_id.begin, |a| a.private.saturating_accrue(size));
+		InstaPoolIo::<T>::mutate(sale.region_end, |a| a.private.saturating_reduce(size));
+		let record = ContributionRecord { length: duration, payee: contribution.payee };
+		InstaPoolContribution::<T>::insert(&region_id, record);
+		Self::deposit_event(Event::Pooled { region_id, duration });
+		Ok(region_id)
+	}
+
+	/// Pay out the revenue owed for the contribution of `region` to the Pool, up to
+	/// `max_timeslices`. It goes to the payee unless `maybe_dest` gives the payee together with a
+	/// different account to which it should go instead.
 	pub(crate) fn do_claim_revenue(
-		mut region: RegionId,
+		region: RegionId,
+		max_timeslices: Timeslice,
+		maybe_dest: Option<(T::AccountId, T::AccountId)>,
+	) -> DispatchResult {
+		if let Some((ref who, _)) = maybe_dest {
+			let contribution =
+				InstaPoolContribution::<T>::get(region).ok_or(Error::<T>::UnknownContribution)?;
+			ensure!(&contribution.payee == who, Error::<T>::NotOwner);
+		}
+		let (payee, payout, next) = Self::take_revenue(region, max_timeslices)?;
+		let who = maybe_dest.map_or(payee, |(_, dest)| dest);
+		T::Currency::transfer(&Self::account_id(), &who, payout, Expendable).defensive_ok();
+		Self::deposit_event(Event::RevenueClaimPaid { who, amount: payout, next });
+		Ok(())
+	}
+
+	/// Claim the revenue owed to `who` for the contribution of `region` to the Pool, turning it
+	/// into Instantaneous Coretime Pool credit for `beneficiary` rather than paying it out.
+	pub(crate) fn do_compound_revenue(
+		who: T::AccountId,
+		region: RegionId,
 		max_timeslices: Timeslice,
+		beneficiary: RelayAccountIdOf<T>,
 	) -> DispatchResult {
+		let contribution =
+			InstaPoolContribution::<T>::get(region).ok_or(Error::<T>::UnknownContribution)?;
+		ensure!(contribution.payee == who, Error::<T>::NotOwner);
+		let (_, amount, next) = Self::take_revenue(region, max_timeslices)?;
+		// The revenue is already held by the pallet, so it merely needs to be credited.
+		Self::issue_credit(beneficiary.clone(), amount);
+		Self::deposit_event(Event::RevenueCompounded { who, beneficiary, amount, next });
+		Ok(())
+	}
+
+	/// Remove up to `max_timeslices` of the revenue owed for the contribution of `region` to the
+	/// Pool from the pool history, returning the payee, the amount owed and the region to claim
+	/// any remainder with.
+	pub(crate) fn take_revenue(
+		mut region: RegionId,
+		max_timeslices: Timeslice,
+	) -> Result<(T::AccountId, BalanceOf<T>, Option<RegionId>), DispatchError> {
 		let mut contribution =
 			InstaPoolContribution::<T>::take(region).ok_or(Error::<T>::UnknownContribution)?;
 		let contributed_parts = region.mask.count_ones();
@@ -328,7 +858,7 @@ impl<T: Config> Pallet<T> {
 		Self::deposit_event(Event::RevenueClaimBegun { region, max_timeslices });
 
 		let mut payout = BalanceOf::<T>::zero();
-		let last = region.begin + contribution.length.min(max_timeslices);
+		let last = region.begin.saturating_add(contribution.length.min(max_timeslices));
 		for r in region.begin..last {
 			region.begin = r + 1;
 			contribution.length.saturating_dec();
@@ -339,12 +869,13 @@ impl<T: Config> Pallet<T> {
 			let Some(total_payout) = pool_record.maybe_payout else {
 				break;
 			};
-			let p = total_payout
-				.saturating_mul(contributed_parts.into())
-				.checked_div(&pool_record.private_contributions.into())
-				.unwrap_or_default();
+			// The share is of the parts still awaiting payment from the pooled total, not of the
+			// whole core, so parts assigned to tasks take nothing and the last claimant receives
+			// any remainder left by rounding.
+			let p =
+				Self::pro_rata(total_payout, contributed_parts, pool_record.private_contributions);
 
-			payout.saturating_accrue(p);
+			payout = payout.checked_add(&p).ok_or(Error::<T>::RevenueOverflow)?;
 			pool_record.private_contributions.saturating_reduce(contributed_parts);
 
 			let remaining_payout = total_payout.saturating_sub(p);
@@ -362,15 +893,9 @@ impl<T: Config> Pallet<T> {
 		if contribution.length > 0 {
 			InstaPoolContribution::<T>::insert(region, &contribution);
 		}
-		T::Currency::transfer(&Self::account_id(), &contribution.payee, payout, Expendable)
-			.defensive_ok();
-		let next = if last < region.begin + contribution.length { Some(region) } else { None };
-		Self::deposit_event(Event::RevenueClaimPaid {
-			who: contribution.payee,
-			amount: payout,
-			next,
-		});
-		Ok(())
+		let end = region.begin.saturating_add(contribution.length);
+		let next = if last < end { Some(region) } else { None };
+		Ok((contribution.payee, payout, next))
 	}
 
 	pub(crate) fn do_purchase_credit(
@@ -379,23 +904,109 @@ impl<T: Config> Pallet<T> {
 		beneficiary: RelayAccountIdOf<T>,
 	) -> DispatchResult {
 		T::Currency::transfer(&who, &Self::account_id(), amount, Expendable)?;
-		let rc_amount = T::ConvertBalance::convert(amount);
-		T::Coretime::credit_account(beneficiary.clone(), rc_amount);
+		Self::issue_credit(beneficiary.clone(), amount);
 		Self::deposit_event(Event::<T>::CreditPurchased { who, beneficiary, amount });
 		Ok(())
 	}
 
+	/// Move `amount` of the unspent credit of the Relay-chain account controlled by `who` to `to`.
+	/// The credit is granted to `to` afresh, expiring `CreditExpiry` timeslices from now. Like any
+	/// spending, it comes out of the oldest grants to `from`, so their expiries find no more
+	/// unspent credit than is left of them and never reach into later grants.
+	pub(crate) fn do_transfer_credit(
+		who: T::AccountId,
+		to: RelayAccountIdOf<T>,
+		amount: BalanceOf<T>,
+	) -> DispatchResult {
+		let from = T::ConvertAccount::convert(who);
+		let rc_amount = T::ConvertBalance::convert(amount);
+		let revoked = T::Coretime::revoke_credit(from.clone(), rc_amount);
+		if revoked < rc_amount {
+			T::Coretime::credit_account(from, revoked);
+			return Err(Error::<T>::InsufficientCredit.into())
+		}
+		Self::issue_credit(to.clone(), amount);
+		Self::deposit_event(Event::<T>::CreditTransferred { from, to, amount });
+		Ok(())
+	}
+
+	pub(crate) fn do_place_order(
+		who: T::AccountId,
+		task: TaskId,
+		max_amount: BalanceOf<T>,
+	) -> DispatchResult {
+		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
+		ensure!(
+			status.private_pool_size.saturating_add(status.system_pool_size) > 0,
+			Error::<T>::Unavailable
+		);
+		let price = Self::spot_price()?;
+		ensure!(max_amount >= price, Error::<T>::Overpriced);
+
+		let next_block = T::Coretime::latest().saturating_add(One::one());
+		let when: Timeslice = (next_block / T::TimeslicePeriod::get()).saturated_into();
+		T::Currency::transfer(&who, &Self::account_id(), price, Expendable)?;
+		InstaPoolOrderRevenue::<T>::try_mutate(when, |r| -> DispatchResult {
+			*r = r.checked_add(&price).ok_or(Error::<T>::RevenueOverflow)?;
+			Ok(())
+		})?;
+		T::Coretime::place_order(task, next_block, T::ConvertBalance::convert(price));
+		Self::deposit_event(Event::<T>::OrderPlaced { who, task, when, price });
+		Ok(())
+	}
+
 	pub(crate) fn do_drop_region(region_id: RegionId) -> DispatchResult {
 		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
 		let region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
 		ensure!(status.last_committed_timeslice >= region.end, Error::<T>::StillValid);
 
 		Regions::<T>::remove(&region_id);
+		RegionMetadata::<T>::remove(&region_id);
+		Self::refund_region_deposit(RegionDeposits::<T>::take(&region_id));
 		let duration = region.end.saturating_sub(region_id.begin);
 		Self::deposit_event(Event::RegionDropped { region_id, duration });
 		Ok(())
 	}
 
+	/// Drop `region_id`, whose core no longer exists, refunding its owner through `OnRefund` for
+	/// the proportion of the price paid which covers the part of the Region not yet begun.
+	pub(crate) fn do_claim_refund(
+		region_id: RegionId,
+		maybe_check_owner: Option<T::AccountId>,
+	) -> DispatchResult {
+		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
+		let region = Regions::<T>::get(&region_id).ok_or(Error::<T>::UnknownRegion)?;
+		if let Some(check_owner) = maybe_check_owner {
+			ensure!(check_owner == region.owner, Error::<T>::NotOwner);
+		}
+		ensure!(region_id.core >= status.core_count, Error::<T>::NotRefundable);
+
+		// Interlaced Regions each keep the full price paid, so it is also pro-rated by the part of
+		// the core the Region covers.
+		let from = region_id.begin.max(status.last_committed_timeslice.saturating_add(1));
+		let remaining = region.end.saturating_sub(from);
+		let length = region.end.saturating_sub(region_id.begin);
+		let amount = region
+			.paid
+			.map(|paid| {
+				paid.saturating_mul(remaining.into())
+					.saturating_mul(region_id.mask.count_ones().into())
+					.checked_div(&length.saturating_mul(CORE_MASK_BITS as u32).into())
+					.unwrap_or_default()
+			})
+			.unwrap_or_default();
+		if !amount.is_zero() {
+			T::OnRefund::on_refund(&region.owner, amount)?;
+		}
+
+		Regions::<T>::remove(&region_id);
+		Listings::<T>::remove(&region_id);
+		RegionMetadata::<T>::remove(&region_id);
+		Self::refund_region_deposit(RegionDeposits::<T>::take(&region_id));
+		Self::deposit_event(Event::RegionRefunded { region_id, who: region.owner, amount });
+		Ok(())
+	}
+
 	pub(crate) fn do_drop_contribution(region_id: RegionId) -> DispatchResult {
 		let config = Configuration::<T>::get().ok_or(Error::<T>::Uninitialized)?;
 		let status = Status::<T>::get().ok_or(Error::<T>::Uninitialized)?;
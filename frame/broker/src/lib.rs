@@ -21,13 +21,17 @@
 pub use pallet::*;
 
 mod adapt_price;
+mod asset_price;
 mod benchmarking;
 mod core_mask;
 mod coretime_interface;
 mod dispatchable_impls;
+pub mod migration;
 #[cfg(test)]
 mod mock;
 mod nonfungible_impl;
+mod on_refund;
+mod on_reserve;
 #[cfg(test)]
 mod test_fungibles;
 #[cfg(test)]
@@ -40,28 +44,38 @@ pub mod weights;
 pub use weights::WeightInfo;
 
 pub use adapt_price::*;
+pub use asset_price::*;
 pub use core_mask::*;
 pub use coretime_interface::*;
 pub use nonfungible_impl::*;
+pub use on_refund::*;
+pub use on_reserve::*;
 pub use types::*;
 pub use utility_impls::*;
 
+pub const LOG_TARGET: &str = "runtime::broker";
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 	use frame_support::{
 		pallet_prelude::{DispatchResult, DispatchResultWithPostInfo, *},
 		traits::{
-			fungible::{Balanced, Credit, Mutate},
-			EnsureOrigin, OnUnbalanced,
+			fungible::{Balanced, Credit, Mutate, MutateHold},
+			fungibles, EnsureOrigin, OnUnbalanced,
 		},
 		PalletId,
 	};
 	use frame_system::pallet_prelude::*;
+	use sp_arithmetic::Perbill;
 	use sp_runtime::traits::{Convert, ConvertBack};
 	use sp_std::vec::Vec;
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
@@ -72,7 +86,12 @@ pub mod pallet {
 		type WeightInfo: WeightInfo;
 
 		/// Currency used to pay for Coretime.
-		type Currency: Mutate<Self::AccountId> + Balanced<Self::AccountId>;
+		type Currency: Mutate<Self::AccountId>
+			+ Balanced<Self::AccountId>
+			+ MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>;
+
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
 
 		/// The origin test needed for administrating this pallet.
 		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
@@ -92,6 +111,10 @@ pub mod pallet {
 		type ConvertBalance: Convert<BalanceOf<Self>, RelayBalanceOf<Self>>
 			+ ConvertBack<BalanceOf<Self>, RelayBalanceOf<Self>>;
 
+		/// Conversion from a local account to the Relay-chain account which it controls, used to
+		/// determine whose Instantaneous Coretime Market Credit a signed origin may transfer.
+		type ConvertAccount: Convert<Self::AccountId, RelayAccountIdOf<Self>>;
+
 		/// Identifier from which the internal Pot is generated.
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
@@ -107,6 +130,62 @@ pub mod pallet {
 		/// Maximum number of system cores.
 		#[pallet::constant]
 		type MaxReservedCores: Get<u32>;
+
+		/// Maximum length in bytes of the metadata which may be attached to a Region.
+		#[pallet::constant]
+		type MaxMetadataLen: Get<u32>;
+
+		/// The lowest price at which Bulk Coretime will be sold, regardless of the price computed
+		/// for the sale.
+		#[pallet::constant]
+		type MinPrice: Get<BalanceOf<Self>>;
+
+		/// The deposit held from the creator of each Region for its storage, released once the
+		/// Region is removed.
+		#[pallet::constant]
+		type RegionDeposit: Get<BalanceOf<Self>>;
+
+		/// Whether cores left unsold at the end of a sale are placed into the Instantaneous
+		/// Coretime Pool for its duration, with the revenue going to `OnRevenue`. Otherwise they
+		/// are left idle.
+		#[pallet::constant]
+		type AutoPoolUnsold: Get<bool>;
+
+		/// The number of timeslices after which any Instantaneous Coretime Pool revenue for a
+		/// timeslice which has not been claimed is passed to `OnRevenue`.
+		#[pallet::constant]
+		type RevenueClaimPeriod: Get<Timeslice>;
+
+		/// The number of timeslices after which unspent Instantaneous Coretime Market Credit
+		/// expires.
+		#[pallet::constant]
+		type CreditExpiry: Get<Timeslice>;
+
+		/// The weight given to the price of the most recently concluded sale when updating
+		/// `PriceEma`.
+		#[pallet::constant]
+		type PriceEmaFactor: Get<Perbill>;
+
+		/// The number of Relay-chain blocks past the next sale boundary for which a core's
+		/// assignment is hinted to remain in place when the core's workload changes there,
+		/// covering for the assignment of the next sale being committed late. Zero gives no hint.
+		#[pallet::constant]
+		type AssignmentGrace: Get<RelayBlockNumberOf<Self>>;
+
+		/// Assets other than `Currency` which may be used to pay for the purchase of Coretime.
+		type Assets: fungibles::Mutate<Self::AccountId> + fungibles::Balanced<Self::AccountId>;
+
+		/// What to do with any revenues collected in `Assets` from the sale of Coretime.
+		type OnAssetRevenue: OnUnbalanced<fungibles::Credit<Self::AccountId, Self::Assets>>;
+
+		/// Determines which of `Assets` may be used as payment and the amount due in them.
+		type AssetPrice: AssetPrice<AssetIdOf<Self>, BalanceOf<Self>, AssetBalanceOf<Self>>;
+
+		/// Told of each core reserved for a workload, and able to charge for or refuse it.
+		type OnReserve: OnReserve;
+
+		/// Pays the refund due for a Region whose core has been removed.
+		type OnRefund: OnRefund<Self::AccountId, BalanceOf<Self>>;
 	}
 
 	/// The current configuration of this pallet.
@@ -129,6 +208,27 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type SaleInfo<T> = StorageValue<_, SaleInfoRecordOf<T>, OptionQuery>;
 
+	/// The core of the current sale which is being sold in parts, together with the parts of it
+	/// which have been sold so far.
+	#[pallet::storage]
+	pub type PartiallySoldCore<T> = StorageValue<_, (CoreIndex, CoreMask), OptionQuery>;
+
+	/// The cores of the current sale which were purchased by index, out of the usual order, in
+	/// ascending order.
+	#[pallet::storage]
+	pub type ChosenCores<T> = StorageValue<_, ChosenCoresRecord, ValueQuery>;
+
+	/// Set once the current sale has been cancelled, to the timeslice from which its unsold
+	/// cores were placed into the Instantaneous Coretime Pool. No more cores may be purchased in
+	/// the sale, but renewals may still take the pooled cores.
+	#[pallet::storage]
+	pub type CancelledSale<T> = StorageValue<_, Timeslice, OptionQuery>;
+
+	/// The number of sales which have been initialized, and thus the index of the current sale
+	/// counting from one.
+	#[pallet::storage]
+	pub type SaleIndex<T> = StorageValue<_, u32, ValueQuery>;
+
 	/// Records of allowed renewals.
 	#[pallet::storage]
 	pub type AllowedRenewals<T> =
@@ -161,6 +261,66 @@ pub mod pallet {
 	pub type InstaPoolHistory<T> =
 		StorageMap<_, Blake2_128Concat, Timeslice, InstaPoolHistoryRecordOf<T>>;
 
+	/// Revenue from on-demand orders for each Timeslice, to be paid out to the Instantaneous
+	/// Coretime Pool along with its revenue reported by the Relay-chain.
+	#[pallet::storage]
+	pub type InstaPoolOrderRevenue<T> =
+		StorageMap<_, Blake2_128Concat, Timeslice, BalanceOf<T>, ValueQuery>;
+
+	/// The amounts of Instantaneous Coretime Market Credit granted to each Relay-chain account,
+	/// keyed by the timeslice at which they expire.
+	#[pallet::storage]
+	pub type CreditExpiries<T> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		Timeslice,
+		Blake2_128Concat,
+		RelayAccountIdOf<T>,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// The total Instantaneous Coretime Market Credit granted to each Relay-chain account which
+	/// has yet to expire, whether or not it has since been spent. Credit is spent oldest grant
+	/// first, so this tells how much of an expiring grant is still unspent.
+	#[pallet::storage]
+	pub type CreditOutstanding<T> =
+		StorageMap<_, Blake2_128Concat, RelayAccountIdOf<T>, BalanceOf<T>, ValueQuery>;
+
+	/// The earliest timeslice whose expired credit may not yet have been fully swept.
+	#[pallet::storage]
+	pub type CreditExpiryCursor<T> = StorageValue<_, Timeslice, OptionQuery>;
+
+	/// The last Instantaneous Coretime Pool contribution visited by the sweep of unclaimed
+	/// revenue, after which the next sweep continues.
+	#[pallet::storage]
+	pub type RevenueSweepCursor<T> = StorageValue<_, RegionId, OptionQuery>;
+
+	/// Revenue reported by the Relay-chain through `notify_revenue` which is yet to be processed.
+	#[pallet::storage]
+	pub type RevenueInbox<T> = StorageValue<_, RevenueInfoOf<T>, OptionQuery>;
+
+	/// The exponential moving average of the prices at which sales concluded, updated as each
+	/// sale is rotated out. `None` until the first sale which offered any cores has concluded.
+	#[pallet::storage]
+	pub type PriceEma<T> = StorageValue<_, BalanceOf<T>, OptionQuery>;
+
+	/// Regions listed for sale by their owner, together with the owner and the asking price.
+	#[pallet::storage]
+	pub type Listings<T> =
+		StorageMap<_, Blake2_128Concat, RegionId, (T::AccountId, BalanceOf<T>), OptionQuery>;
+
+	/// The storage deposits held for Regions, together with the account which placed them.
+	#[pallet::storage]
+	pub type RegionDeposits<T> =
+		StorageMap<_, Blake2_128Concat, RegionId, (T::AccountId, BalanceOf<T>), OptionQuery>;
+
+	/// Short labels attached to Regions by their owner. Cleared once the Region changes hands or
+	/// ceases to exist.
+	#[pallet::storage]
+	pub type RegionMetadata<T> =
+		StorageMap<_, Blake2_128Concat, RegionId, RegionMetadataOf<T>, OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -408,6 +568,121 @@ pub mod pallet {
 			/// The core whose workload is no longer available to be renewed for `when`.
 			core: CoreIndex,
 		},
+		/// A Region has been listed for sale by its owner.
+		RegionListed {
+			/// The Region which has been listed.
+			region_id: RegionId,
+			/// The owner of the Region.
+			seller: T::AccountId,
+			/// The price asked for the Region.
+			price: BalanceOf<T>,
+		},
+		/// A Region is no longer listed for sale.
+		RegionUnlisted {
+			/// The Region which has been unlisted.
+			region_id: RegionId,
+		},
+		/// A listed Region has been bought.
+		ListedRegionSold {
+			/// The Region which has been sold.
+			region_id: RegionId,
+			/// The previous owner of the Region.
+			seller: T::AccountId,
+			/// The new owner of the Region.
+			buyer: T::AccountId,
+			/// The price paid for the Region.
+			price: BalanceOf<T>,
+		},
+		/// An on-demand order for Coretime from the Instantaneous Coretime Pool has been placed.
+		OrderPlaced {
+			/// The account which placed and paid for the order.
+			who: T::AccountId,
+			/// The task which should be scheduled.
+			task: TaskId,
+			/// The timeslice of the Relay-chain block for which the order was placed.
+			when: Timeslice,
+			/// The spot price paid for the order.
+			price: BalanceOf<T>,
+		},
+		/// Unspent Instantaneous Coretime Market Credit has expired and been removed.
+		CreditExpired {
+			/// The Relay-chain account whose credit has expired.
+			who: RelayAccountIdOf<T>,
+			/// The amount of unspent credit which was removed, in local balance.
+			amount: BalanceOf<T>,
+		},
+		/// The ongoing sale has been cancelled and its unsold cores placed into the Instantaneous
+		/// Coretime Pool.
+		SaleCancelled {
+			/// The number of unsold cores which were placed into the Instantaneous Coretime Pool.
+			cores_pooled: CoreIndex,
+		},
+		/// A core left unsold at the end of a sale has been placed into the Instantaneous
+		/// Coretime Pool.
+		UnsoldAutoPooled {
+			/// The Region of the core which was pooled.
+			region_id: RegionId,
+			/// The duration of the Region.
+			duration: Timeslice,
+		},
+		/// The unconsumed part of a contribution to the Instantaneous Coretime Pool has been
+		/// withdrawn and restored as a Region.
+		Unpooled {
+			/// The Region which has been restored.
+			region_id: RegionId,
+			/// The duration of the Region.
+			duration: Timeslice,
+		},
+		/// A revenue claim has (possibly only in part) been turned into Instantaneous Coretime
+		/// Pool credit rather than paid.
+		RevenueCompounded {
+			/// The account to whom the revenue was owed.
+			who: T::AccountId,
+			/// The Relay-chain account to which the credit will be made.
+			beneficiary: RelayAccountIdOf<T>,
+			/// The total amount of revenue claimed and credited.
+			amount: BalanceOf<T>,
+			/// The next region which should be claimed for the continuation of this contribution.
+			next: Option<RegionId>,
+		},
+		/// Some Instantaneous Coretime Pool credit has been transferred.
+		CreditTransferred {
+			/// The Relay-chain account from which the credit was transferred.
+			from: RelayAccountIdOf<T>,
+			/// The Relay-chain account to which the credit was transferred.
+			to: RelayAccountIdOf<T>,
+			/// The amount of credit transferred.
+			amount: BalanceOf<T>,
+		},
+		/// Metadata has been attached to a Region.
+		RegionMetadataSet {
+			/// The Region whose metadata has been set.
+			region_id: RegionId,
+			/// The metadata now attached to the Region.
+			metadata: RegionMetadataOf<T>,
+		},
+		/// The metadata of a Region has been cleared by its owner.
+		RegionMetadataCleared {
+			/// The Region whose metadata has been cleared.
+			region_id: RegionId,
+		},
+		/// Instantaneous Coretime Pool revenue which had gone unclaimed for longer than
+		/// `RevenueClaimPeriod` has been passed to `OnRevenue`.
+		RevenueSwept {
+			/// The contribution whose revenue was swept, as identified before the sweep.
+			region_id: RegionId,
+			/// The amount of revenue swept.
+			amount: BalanceOf<T>,
+		},
+		/// A Region whose core has been removed has been refunded and dropped.
+		RegionRefunded {
+			/// The Region which has been refunded.
+			region_id: RegionId,
+			/// The owner of the Region, to whom the refund was paid.
+			who: T::AccountId,
+			/// The amount refunded.
+			amount: BalanceOf<T>,
+		},
 	}
 
 	#[pallet::error]
@@ -472,6 +747,53 @@ pub mod pallet {
 		AlreadyExpired,
 		/// The configuration could not be applied because it is invalid.
 		InvalidConfig,
+		/// The core of the Region no longer exists since the number of cores has been reduced.
+		CoreNoLongerExists,
+		/// Fewer cores remain on sale than the purchaser required.
+		CoresExhausted,
+		/// The weights of a multi-task assignment add up to zero.
+		ZeroAssignmentWeight,
+		/// The Region is not listed for sale.
+		NotListed,
+		/// The part of the core to be purchased is void.
+		VoidPart,
+		/// The part of the core to be purchased has already been sold.
+		PartUnavailable,
+		/// The items of the reserved workload overlap on some part of the core.
+		OverlappingReservation,
+		/// The Regions are not all of the same core.
+		DifferentCores,
+		/// Revenue reported previously has not yet been processed.
+		RevenuePending,
+		/// Part of the Region to be pooled is already assigned to a task.
+		PartAlreadyAssigned,
+		/// The items of the reserved workload do not together cover the whole core.
+		IncompleteSchedule,
+		/// The asset may not be used to pay for Coretime.
+		UnsupportedAsset,
+		/// The account does not have enough unspent credit.
+		InsufficientCredit,
+		/// The first sale cycle would begin at a timeslice which is already ready to be committed.
+		FirstCycleTooEarly,
+		/// The requested core is not for sale in the ongoing sale or has already been sold.
+		CoreUnavailable,
+		/// Accounting for the revenue would exceed the largest representable balance.
+		RevenueOverflow,
+		/// The contribution to the Instantaneous Coretime Pool was made with `Final` finality and
+		/// may not be withdrawn.
+		FinalContribution,
+		/// The core of the Region still exists, so the Region is not due a refund.
+		NotRefundable,
+		/// More cores are unsold in the sale than the given upper bound.
+		TooManyUnsoldCores,
+	}
+
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// The deposit for the storage of a Region.
+		#[codec(index = 0)]
+		RegionDeposit,
 	}
 
 	#[pallet::hooks]
@@ -479,6 +801,11 @@ pub mod pallet {
 		fn on_initialize(_now: BlockNumberFor<T>) -> Weight {
 			Self::do_tick()
 		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_: BlockNumberFor<T>) -> Result<(), sp_runtime::TryRuntimeError> {
+			Self::do_try_state()
+		}
 	}
 
 	#[pallet::call(weight(<T as Config>::WeightInfo))]
@@ -500,7 +827,9 @@ pub mod pallet {
 		/// Reserve a core for a workload.
 		///
 		/// - `origin`: Must be Root or pass `AdminOrigin`.
-		/// - `workload`: The workload which should be permanently placed on a core.
+		/// - `workload`: The workload which should be permanently placed on a core. Its items must
+		///   not overlap and must together cover the whole core; any part meant to be left unused
+		///   should be assigned `Idle`.
 		#[pallet::call_index(1)]
 		pub fn reserve(origin: OriginFor<T>, workload: Schedule) -> DispatchResultWithPostInfo {
 			T::AdminOrigin::ensure_origin_or_root(origin)?;
@@ -547,15 +876,19 @@ pub mod pallet {
 		/// - `origin`: Must be Root or pass `AdminOrigin`.
 		/// - `initial_price`: The price of Bulk Coretime in the first sale.
 		/// - `core_count`: The number of cores which can be allocated.
+		/// - `first_cycle_start`: The timeslice at which the Regions of the first sale should
+		///   begin, or `None` to begin them one Region's length after the latest timeslice ready to
+		///   be committed.
 		#[pallet::call_index(4)]
 		#[pallet::weight(T::WeightInfo::start_sales((*core_count).into()))]
 		pub fn start_sales(
 			origin: OriginFor<T>,
 			initial_price: BalanceOf<T>,
 			core_count: CoreIndex,
+			first_cycle_start: Option<Timeslice>,
 		) -> DispatchResultWithPostInfo {
 			T::AdminOrigin::ensure_origin_or_root(origin)?;
-			Self::do_start_sales(initial_price, core_count)?;
+			Self::do_start_sales(initial_price, core_count, first_cycle_start)?;
 			Ok(Pays::No.into())
 		}
 
@@ -678,22 +1011,27 @@ pub mod pallet {
 
 		/// Claim the revenue owed from inclusion in the Instantaneous Coretime Pool.
 		///
-		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `origin`: Must be a Signed origin. Any account may trigger the claim; the revenue is
+		///   paid to the payee given when the Region was assigned to the Pool unless `dest` is
+		///   given.
 		/// - `region_id`: The Region which was assigned to the Pool.
 		/// - `max_timeslices`: The maximum number of timeslices which should be processed. This may
 		///   effect the weight of the call but should be ideally made equivalant to the length of
 		///   the Region `region_id`. If it is less than this, then further dispatches will be
 		///   required with the `region_id` which makes up any remainders of the region to be
 		///   collected.
+		/// - `dest`: The account to which the revenue should be paid instead of the payee. Only the
+		///   payee may give this.
 		#[pallet::call_index(12)]
 		#[pallet::weight(T::WeightInfo::claim_revenue(*max_timeslices))]
 		pub fn claim_revenue(
 			origin: OriginFor<T>,
 			region_id: RegionId,
 			max_timeslices: Timeslice,
+			dest: Option<T::AccountId>,
 		) -> DispatchResultWithPostInfo {
-			let _ = ensure_signed(origin)?;
-			Self::do_claim_revenue(region_id, max_timeslices)?;
+			let who = ensure_signed(origin)?;
+			Self::do_claim_revenue(region_id, max_timeslices, dest.map(|dest| (who, dest)))?;
 			Ok(Pays::No.into())
 		}
 
@@ -780,5 +1118,363 @@ pub mod pallet {
 			Self::do_request_core_count(core_count)?;
 			Ok(())
 		}
+
+		/// Purchase Bulk Coretime in the ongoing Sale, provided that enough cores remain on sale.
+		///
+		/// - `origin`: Must be a Signed origin with at least enough funds to pay the current price
+		///   of Bulk Coretime.
+		/// - `price_limit`: An amount no more than which should be paid.
+		/// - `min_cores_remaining`: The number of cores which must still be on sale (including the
+		///   one being purchased) for the purchase to go ahead.
+		#[pallet::call_index(19)]
+		pub fn purchase_guarded(
+			origin: OriginFor<T>,
+			price_limit: BalanceOf<T>,
+			min_cores_remaining: CoreIndex,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_purchase_guarded(who, price_limit, min_cores_remaining)?;
+			Ok(Pays::No.into())
+		}
+
+		/// Renew a pooled Region of Bulk Coretime in the ongoing Sale or its prior Interlude
+		/// Period and place the renewed Region into the Instantaneous Coretime Pool with the same
+		/// payee.
+		///
+		/// - `origin`: Must be a Signed origin with at least enough funds to pay the renewal price
+		///   of the core.
+		/// - `region_id`: The whole-core Region which was pooled with `Final` finality and ends at
+		///   the beginning of the Regions of the ongoing Sale.
+		#[pallet::call_index(20)]
+		pub fn renew_pooled(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_renew_pooled(who, region_id)?;
+			Ok(Pays::No.into())
+		}
+
+		/// Cancel the ongoing sale, placing all of its unsold cores into the Instantaneous
+		/// Coretime Pool for the duration of its Regions. No further cores can be bought or renewed
+		/// in the sale.
+		///
+		/// - `origin`: Must be Root or pass `AdminOrigin`.
+		/// - `max_cores`: An upper bound on the number of unsold cores which will be pooled.
+		#[pallet::call_index(21)]
+		#[pallet::weight(T::WeightInfo::cancel_sale((*max_cores).into()))]
+		pub fn cancel_sale(
+			origin: OriginFor<T>,
+			max_cores: CoreIndex,
+		) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin_or_root(origin)?;
+			let cores_pooled = Self::do_cancel_sale(max_cores)?;
+			Ok((Some(T::WeightInfo::cancel_sale(cores_pooled.into())), Pays::No).into())
+		}
+
+		/// Assign a Bulk Coretime Region to several tasks, sharing its parts between them
+		/// according to their weights.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `region_id`: The Region which should be assigned to the tasks.
+		/// - `assignments`: The tasks to assign, each with its relative weight.
+		/// - `finality`: Indication of whether this assignment is final (in which case it may be
+		///   eligible for renewal) or provisional (in which case it may be manipulated and/or
+		/// reassigned at a later stage).
+		#[pallet::call_index(22)]
+		pub fn assign_multi(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+			assignments: WeightedAssignments,
+			finality: Finality,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_assign_multi(region_id, Some(who), assignments, finality)?;
+			Ok(if finality == Finality::Final { Pays::No } else { Pays::Yes }.into())
+		}
+
+		/// List a Bulk Coretime Region for sale at a fixed price. Any existing listing of the
+		/// Region is replaced.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `region_id`: The Region which should be listed.
+		/// - `price`: The price at which the Region may be bought.
+		#[pallet::call_index(23)]
+		pub fn list_region(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+			price: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_list_region(region_id, who, price)?;
+			Ok(())
+		}
+
+		/// Remove the listing of a Bulk Coretime Region.
+		///
+		/// - `origin`: Must be a Signed origin of the account which listed the Region `region_id`.
+		/// - `region_id`: The Region which should no longer be listed.
+		#[pallet::call_index(24)]
+		pub fn unlist_region(origin: OriginFor<T>, region_id: RegionId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_unlist_region(region_id, Some(who))?;
+			Ok(())
+		}
+
+		/// Buy a listed Bulk Coretime Region, paying its owner the asking price.
+		///
+		/// - `origin`: Must be a Signed origin with at least enough funds to pay the asking price.
+		/// - `region_id`: The listed Region which should be bought.
+		/// - `price_limit`: An amount no more than which should be paid.
+		#[pallet::call_index(25)]
+		pub fn buy_listed(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+			price_limit: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_buy_listed(who, region_id, price_limit)?;
+			Ok(())
+		}
+
+		/// Place an on-demand order for Coretime from the Instantaneous Coretime Pool for the next
+		/// Relay-chain block, paying the current spot price.
+		///
+		/// - `origin`: Must be a Signed origin able to pay at least the spot price.
+		/// - `task`: The task which should be scheduled.
+		/// - `max_amount`: An amount no more than which should be paid.
+		#[pallet::call_index(26)]
+		pub fn place_order(
+			origin: OriginFor<T>,
+			task: TaskId,
+			max_amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_place_order(who, task, max_amount)?;
+			Ok(())
+		}
+
+		/// Set the reservation at a given index, either replacing an existing reservation or
+		/// appending a new one. The change takes effect from the next sale rotation.
+		///
+		/// - `origin`: Must be Root or pass `AdminOrigin`.
+		/// - `item_index`: The index of the reservation, at most the number of reservations.
+		/// - `workload`: The workload which should be permanently placed on a core. Its items must
+		///   not overlap and must together cover the whole core; any part meant to be left unused
+		///   should be assigned `Idle`.
+		#[pallet::call_index(27)]
+		pub fn set_reservation(
+			origin: OriginFor<T>,
+			item_index: u32,
+			workload: Schedule,
+		) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin_or_root(origin)?;
+			Self::do_set_reservation(item_index, workload)?;
+			Ok(Pays::No.into())
+		}
+
+		/// Purchase part of a core of Bulk Coretime in the ongoing Sale, at the corresponding
+		/// proportion of the current price. The rest of the core remains available to be purchased
+		/// in parts.
+		///
+		/// - `origin`: Must be a Signed origin with at least enough funds to pay the price of the
+		///   part.
+		/// - `part`: The part of the core which should be purchased.
+		/// - `price_limit`: An amount no more than which should be paid.
+		#[pallet::call_index(28)]
+		pub fn purchase_part(
+			origin: OriginFor<T>,
+			part: CoreMask,
+			price_limit: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_purchase_part(who, part, price_limit)?;
+			Ok(Pays::No.into())
+		}
+
+		/// Split each of a number of Bulk Coretime Regions of the same core into two
+		/// wholly-overlapping Regions along the same interlace mask.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Regions.
+		/// - `region_ids`: The Regions which should each become two interlaced Regions.
+		/// - `pivot`: The interlace mask of one of the two new regions of each Region (the other is
+		///   its partial complement).
+		#[pallet::call_index(29)]
+		#[pallet::weight(T::WeightInfo::interlace_range(region_ids.len() as u32))]
+		pub fn interlace_range(
+			origin: OriginFor<T>,
+			region_ids: CoreRegions,
+			pivot: CoreMask,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_interlace_range(region_ids, Some(who), pivot)?;
+			Ok(())
+		}
+
+		/// Report the revenue of the Instantaneous Coretime Pool up to some Relay-chain block, to
+		/// be paid out to its contributors.
+		///
+		/// - `origin`: Must be Root or pass `AdminOrigin`.
+		/// - `revenue`: The revenue made by the Pool and the Relay-chain block up to which it was
+		///   made.
+		#[pallet::call_index(30)]
+		pub fn notify_revenue(origin: OriginFor<T>, revenue: RevenueInfoOf<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin_or_root(origin)?;
+			Self::do_notify_revenue(revenue)?;
+			Ok(())
+		}
+
+		/// Claim the revenue owed from inclusion in the Instantaneous Coretime Pool as credit for
+		/// use in the Pool, rather than having it paid out.
+		///
+		/// - `origin`: Must be a Signed origin of the payee given when the Region was assigned to
+		///   the Pool.
+		/// - `region_id`: The Region which was assigned to the Pool.
+		/// - `max_timeslices`: The maximum number of timeslices which should be processed, as for
+		///   `claim_revenue`.
+		/// - `beneficiary`: The account on the Relay-chain which controls the credit.
+		#[pallet::call_index(31)]
+		#[pallet::weight(T::WeightInfo::compound_revenue(*max_timeslices))]
+		pub fn compound_revenue(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+			max_timeslices: Timeslice,
+			beneficiary: RelayAccountIdOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_compound_revenue(who, region_id, max_timeslices, beneficiary)?;
+			Ok(())
+		}
+
+		/// Withdraw the part of a Region placed provisionally into the Instantaneous Coretime Pool
+		/// which has not yet been used by the Pool, so that it may be assigned instead.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `region_id`: The Region which was assigned to the Pool with `Provisional` finality.
+		/// - `max_timeslices`: The maximum number of timeslices used by the Pool whose revenue
+		///   should be paid out first. Any remainder may be collected with `claim_revenue`.
+		#[pallet::call_index(32)]
+		#[pallet::weight(T::WeightInfo::unpool(*max_timeslices))]
+		pub fn unpool(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+			max_timeslices: Timeslice,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_unpool(region_id, Some(who), max_timeslices)?;
+			Ok(())
+		}
+
+		/// Purchase Bulk Coretime in the ongoing Sale, paying in an asset other than the native
+		/// currency.
+		///
+		/// - `origin`: Must be a Signed origin with at least enough of `asset` to pay the
+		///   equivalent of the current price.
+		/// - `asset`: The asset to pay with, which must be accepted by `AssetPrice`.
+		/// - `price_limit`: An amount of the native currency, the equivalent of which the origin is
+		///   willing to pay at most.
+		#[pallet::call_index(33)]
+		pub fn purchase_with_asset(
+			origin: OriginFor<T>,
+			asset: AssetIdOf<T>,
+			price_limit: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_purchase_with(who, price_limit, Some(asset))?;
+			Ok(Pays::No.into())
+		}
+
+		/// Split a Bulk Coretime Region into two non-overlapping Regions at a fraction of its
+		/// length.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `region_id`: The Region which should be partitioned into two non-overlapping Regions.
+		/// - `fraction`: The proportion of the Region's length, rounded to the nearest timeslice
+		///   with halves rounded down, which the first of the new Regions should cover.
+		#[pallet::call_index(34)]
+		pub fn partition_fraction(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+			fraction: Perbill,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_partition_fraction(region_id, Some(who), fraction)?;
+			Ok(())
+		}
+
+		/// Transfer unspent Instantaneous Coretime Market Credit to another account.
+		///
+		/// - `origin`: Must be a Signed origin of the account controlling the Relay-chain account
+		///   which holds at least `amount` of unspent credit.
+		/// - `to`: The account on the Relay-chain which should control the credit.
+		/// - `amount`: The amount of credit to transfer.
+		#[pallet::call_index(35)]
+		pub fn transfer_credit(
+			origin: OriginFor<T>,
+			to: RelayAccountIdOf<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_transfer_credit(who, to, amount)?;
+			Ok(())
+		}
+
+		/// Purchase a specific core of Bulk Coretime in the ongoing Sale.
+		///
+		/// - `origin`: Must be a Signed origin with at least enough funds to pay the current price
+		///   of Bulk Coretime.
+		/// - `core`: The index of the core to be purchased. It must be on offer in the ongoing sale
+		///   and not yet sold.
+		/// - `price_limit`: An amount no more than which should be paid.
+		#[pallet::call_index(36)]
+		pub fn purchase_core(
+			origin: OriginFor<T>,
+			core: CoreIndex,
+			price_limit: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_purchase_core(who, core, price_limit)?;
+			Ok(Pays::No.into())
+		}
+
+		/// Attach a short label to a Region, replacing any it already has. The label is cleared
+		/// when the Region is transferred, assigned, pooled, interlaced or dropped.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `region_id`: The Region to be labelled.
+		/// - `metadata`: The label to attach.
+		#[pallet::call_index(37)]
+		pub fn set_region_metadata(
+			origin: OriginFor<T>,
+			region_id: RegionId,
+			metadata: RegionMetadataOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_set_region_metadata(region_id, Some(who), metadata)?;
+			Ok(())
+		}
+
+		/// Remove the label attached to a Region.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `region_id`: The Region whose label should be removed.
+		#[pallet::call_index(38)]
+		pub fn clear_region_metadata(origin: OriginFor<T>, region_id: RegionId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_clear_region_metadata(region_id, Some(who))?;
+			Ok(())
+		}
+
+		/// Drop a Region whose core has been removed, refunding its owner for the part of it which
+		/// has not yet begun.
+		///
+		/// - `origin`: Must be a Signed origin of the account which owns the Region `region_id`.
+		/// - `region_id`: The Region to be refunded. Its core must no longer exist.
+		#[pallet::call_index(39)]
+		pub fn claim_refund(origin: OriginFor<T>, region_id: RegionId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_claim_refund(region_id, Some(who))?;
+			Ok(())
+		}
 	}
 }
@@ -21,7 +21,8 @@ use crate::{test_fungibles::TestFungibles, *};
 use frame_support::{
 	assert_ok, ensure, ord_parameter_types, parameter_types,
 	traits::{
-		fungible::{Balanced, Credit, Inspect, ItemOf, Mutate},
+		fungible::{Balanced, Credit, Inspect, InspectHold, ItemOf, Mutate},
+		fungibles,
 		nonfungible::Inspect as NftInspect,
 		EitherOfDiverse, Hooks, OnUnbalanced,
 	},
@@ -32,7 +33,7 @@ use sp_arithmetic::Perbill;
 use sp_core::{ConstU16, ConstU32, ConstU64, H256};
 use sp_runtime::{
 	traits::{BlakeTwo256, Identity, IdentityLookup},
-	BuildStorage, Saturating,
+	BuildStorage, DispatchError, DispatchResult, Saturating,
 };
 use sp_std::collections::btree_map::BTreeMap;
 
@@ -88,6 +89,7 @@ parameter_types! {
 	pub static CoretimeTrace: Vec<(u32, CoretimeTraceItem)> = Default::default();
 	pub static CoretimeCredit: BTreeMap<u64, u64> = Default::default();
 	pub static CoretimeSpending: Vec<(u32, u64)> = Default::default();
+	pub static CoretimeOrders: Vec<(u32, TaskId, u64)> = Default::default();
 	pub static CoretimeWorkplan: BTreeMap<(u32, CoreIndex), Vec<(CoreAssignment, PartsOf57600)>> = Default::default();
 	pub static CoretimeUsage: BTreeMap<CoreIndex, Vec<(CoreAssignment, PartsOf57600)>> = Default::default();
 	pub static CoretimeInPool: CoreMaskBitCount = 0;
@@ -127,6 +129,17 @@ impl CoretimeInterface for TestCoretimeProvider {
 	fn credit_account(who: Self::AccountId, amount: Self::Balance) {
 		CoretimeCredit::mutate(|c| c.entry(who).or_default().saturating_accrue(amount));
 	}
+	fn revoke_credit(who: Self::AccountId, amount: Self::Balance) -> Self::Balance {
+		CoretimeCredit::mutate(|c| {
+			let credit = c.entry(who).or_default();
+			let revoked = amount.min(*credit);
+			credit.saturating_reduce(revoked);
+			revoked
+		})
+	}
+	fn place_order(task: TaskId, begin: Self::BlockNumber, amount: Self::Balance) {
+		CoretimeOrders::mutate(|o| o.push((begin, task, amount)));
+	}
 	fn assign_core(
 		core: CoreIndex,
 		begin: Self::BlockNumber,
@@ -190,6 +203,14 @@ impl TestCoretimeProvider {
 
 parameter_types! {
 	pub const TestBrokerId: PalletId = PalletId(*b"TsBroker");
+	pub static MinPrice: u64 = 0;
+	pub static RegionDeposit: u64 = 0;
+	pub static AutoPoolUnsold: bool = true;
+	pub static CreditExpiry: u32 = 1_000;
+	pub static RevenueClaimPeriod: u32 = 1_000;
+	pub static TimeslicePeriod: u32 = 2;
+	pub static PriceEmaFactor: Perbill = Perbill::from_percent(50);
+	pub static AssignmentGrace: u32 = 0;
 }
 
 pub struct IntoZero;
@@ -199,6 +220,13 @@ impl OnUnbalanced<Credit<u64, <Test as Config>::Currency>> for IntoZero {
 	}
 }
 
+pub struct AssetsIntoZero;
+impl OnUnbalanced<fungibles::Credit<u64, <Test as Config>::Assets>> for AssetsIntoZero {
+	fn on_nonzero_unbalanced(credit: fungibles::Credit<u64, <Test as Config>::Assets>) {
+		let _ = <<Test as Config>::Assets as fungibles::Balanced<_>>::resolve(&0, credit);
+	}
+}
+
 ord_parameter_types! {
 	pub const One: u64 = 1;
 }
@@ -206,17 +234,75 @@ type EnsureOneOrRoot = EitherOfDiverse<EnsureRoot<u64>, EnsureSignedBy<One, u64>
 
 impl crate::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
-	type Currency = ItemOf<TestFungibles<(), u64, (), ConstU64<0>, ()>, (), u64>;
+	type Currency = ItemOf<TestFungibles<(), u64, (), ConstU64<0>, RuntimeHoldReason>, (), u64>;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type OnRevenue = IntoZero;
-	type TimeslicePeriod = ConstU32<2>;
+	type TimeslicePeriod = TimeslicePeriod;
 	type MaxLeasedCores = ConstU32<5>;
 	type MaxReservedCores = ConstU32<5>;
+	type MaxMetadataLen = ConstU32<32>;
 	type Coretime = TestCoretimeProvider;
 	type ConvertBalance = Identity;
+	type ConvertAccount = Identity;
 	type WeightInfo = ();
 	type PalletId = TestBrokerId;
 	type AdminOrigin = EnsureOneOrRoot;
 	type PriceAdapter = Linear;
+	type MinPrice = MinPrice;
+	type RegionDeposit = RegionDeposit;
+	type AutoPoolUnsold = AutoPoolUnsold;
+	type RevenueClaimPeriod = RevenueClaimPeriod;
+	type CreditExpiry = CreditExpiry;
+	type PriceEmaFactor = PriceEmaFactor;
+	type AssignmentGrace = AssignmentGrace;
+	type Assets = TestFungibles<ConstU32<1>, u64, u32, ConstU64<0>, ()>;
+	type OnAssetRevenue = AssetsIntoZero;
+	type AssetPrice = TestAssetPrice;
+	type OnReserve = TestOnReserve;
+	type OnRefund = TestOnRefund;
+}
+
+parameter_types! {
+	pub static ReserveCalls: Vec<(u32, Schedule)> = Default::default();
+	pub static ReservationBudget: Option<u32> = None;
+}
+
+/// Records each reservation, refusing those beyond `ReservationBudget` if it is set.
+pub struct TestOnReserve;
+impl OnReserve for TestOnReserve {
+	fn on_reserve(index: u32, workload: &Schedule) -> DispatchResult {
+		if ReservationBudget::get().map_or(false, |budget| index >= budget) {
+			return Err(DispatchError::Other("Reservation budget exhausted"))
+		}
+		ReserveCalls::mutate(|c| c.push((index, workload.clone())));
+		Ok(())
+	}
+}
+
+/// Pays refunds back out of the revenue collected by `IntoZero`.
+pub struct TestOnRefund;
+impl OnRefund<u64, u64> for TestOnRefund {
+	fn on_refund(who: &u64, amount: u64) -> DispatchResult {
+		<<Test as Config>::Currency as Mutate<_>>::transfer(
+			&0,
+			who,
+			amount,
+			frame_support::traits::tokens::Preservation::Expendable,
+		)
+		.map(|_| ())
+	}
+}
+
+/// Accepts asset `1` at two units per unit of the native currency.
+pub struct TestAssetPrice;
+impl AssetPrice<u32, u64, u64> for TestAssetPrice {
+	fn asset_price(asset: u32, price: u64) -> Option<u64> {
+		(asset == 1).then(|| price.saturating_mul(2))
+	}
+	#[cfg(feature = "runtime-benchmarks")]
+	fn accepted_asset() -> Option<u32> {
+		Some(1)
+	}
 }
 
 pub fn advance_to(b: u64) {
@@ -227,10 +313,52 @@ pub fn advance_to(b: u64) {
 	}
 }
 
+/// Jump to the first block of timeslice `ts`, initializing only those blocks in which the pallet
+/// has something to do rather than every block as `advance_to` does. The pallet ends up in the
+/// same state either way.
+pub fn set_timeslice(ts: Timeslice) {
+	let target = (ts * TimeslicePeriod::get()) as u64;
+	while System::block_number() < target {
+		let next = next_eventful_block().clamp(System::block_number() + 1, target);
+		System::set_block_number(next);
+		TestCoretimeProvider::bump();
+		Broker::on_initialize(next);
+	}
+}
+
+/// The next block from which `on_initialize` may do anything.
+fn next_eventful_block() -> u64 {
+	let now = System::block_number();
+	let (Some(config), Some(status)) = (Configuration::<Test>::get(), Status::<Test>::get()) else {
+		return u64::max_value()
+	};
+	let expiries_pending =
+		CreditExpiryCursor::<Test>::get().map_or(false, |c| c <= Broker::current_timeslice());
+	if !NotifyRevenueInfo::get().is_empty() ||
+		!NotifyCoreCount::get().is_empty() ||
+		expiries_pending
+	{
+		return now + 1
+	}
+	let period = TimeslicePeriod::get() as u64;
+	let next_commit = ((status.last_committed_timeslice as u64 + 1) * period)
+		.saturating_sub(config.advance_notice as u64);
+	let next_timeslice = (status.last_timeslice as u64 + 1) * period;
+	next_commit.min(next_timeslice)
+}
+
 pub fn pot() -> u64 {
 	balance(Broker::account_id())
 }
 
+/// The Region deposits held from `who`.
+pub fn held(who: u64) -> u64 {
+	<<Test as Config>::Currency as InspectHold<_>>::balance_on_hold(
+		&HoldReason::RegionDeposit.into(),
+		&who,
+	)
+}
+
 pub fn revenue() -> u64 {
 	balance(0)
 }
@@ -239,6 +367,10 @@ pub fn balance(who: u64) -> u64 {
 	<<Test as Config>::Currency as Inspect<_>>::total_balance(&who)
 }
 
+pub fn asset_balance(asset: u32, who: u64) -> u64 {
+	<<Test as Config>::Assets as fungibles::Inspect<_>>::balance(asset, &who)
+}
+
 pub fn attribute<T: codec::Decode>(nft: RegionId, attribute: impl codec::Encode) -> T {
 	<Broker as NftInspect<_>>::typed_attribute::<_, T>(&nft.into(), &attribute).unwrap()
 }
@@ -303,11 +435,28 @@ impl TestExt {
 		self
 	}
 
+	pub fn timeslice_period(self, timeslice_period: u32) -> Self {
+		TimeslicePeriod::set(timeslice_period);
+		self
+	}
+
+	pub fn assignment_grace(self, assignment_grace: u32) -> Self {
+		AssignmentGrace::set(assignment_grace);
+		self
+	}
+
 	pub fn endow(self, who: u64, amount: u64) -> Self {
 		assert_ok!(<<Test as Config>::Currency as Mutate<_>>::mint_into(&who, amount));
 		self
 	}
 
+	pub fn endow_asset(self, asset: u32, who: u64, amount: u64) -> Self {
+		assert_ok!(<<Test as Config>::Assets as fungibles::Mutate<_>>::mint_into(
+			asset, &who, amount
+		));
+		self
+	}
+
 	pub fn execute_with<R>(self, f: impl Fn() -> R) -> R {
 		new_test_ext().execute_with(|| {
 			assert_ok!(Broker::do_configure(self.0));
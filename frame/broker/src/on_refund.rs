@@ -0,0 +1,38 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(missing_docs)]
+
+use sp_runtime::{DispatchError, DispatchResult};
+
+/// Type which pays refunds for Bulk Coretime which can no longer be provided, e.g. because the
+/// core of a Region has been removed. The amount refunded was paid to `OnRevenue` at the time of
+/// purchase, so it is up to the implementation to find the funds.
+pub trait OnRefund<AccountId, Balance> {
+	/// Pay `amount` to `who`. Returning an error leaves the refund to be claimed again later.
+	///
+	/// - `who`: The owner of the Region being refunded.
+	/// - `amount`: The part of the price paid for the Region which is to be refunded.
+	fn on_refund(who: &AccountId, amount: Balance) -> DispatchResult;
+}
+
+/// Refuse every refund, so that refunds remain claimable until a runtime provides for them.
+impl<AccountId, Balance> OnRefund<AccountId, Balance> for () {
+	fn on_refund(_: &AccountId, _: Balance) -> DispatchResult {
+		Err(DispatchError::Unavailable)
+	}
+}
@@ -0,0 +1,39 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(missing_docs)]
+
+use crate::Schedule;
+use sp_runtime::DispatchResult;
+
+/// Type which is told of each core reserved for a workload, e.g. to charge a fee for it or to
+/// account for it against a budget.
+pub trait OnReserve {
+	/// Handle the reservation of a core for `workload`, to be stored at `index` of the
+	/// reservations. Returning an error prevents the reservation.
+	///
+	/// - `index`: The index of the new reservation.
+	/// - `workload`: The workload to be placed on the reserved core.
+	fn on_reserve(index: u32, workload: &Schedule) -> DispatchResult;
+}
+
+/// Allow every reservation at no cost.
+impl OnReserve for () {
+	fn on_reserve(_: u32, _: &Schedule) -> DispatchResult {
+		Ok(())
+	}
+}
@@ -19,11 +19,12 @@
 
 use crate::{core_mask::*, mock::*, *};
 use frame_support::{
-	assert_noop, assert_ok,
+	assert_err, assert_noop, assert_ok,
 	traits::nonfungible::{Inspect as NftInspect, Transfer},
 	BoundedVec,
 };
 use frame_system::RawOrigin::Root;
+use sp_arithmetic::Perbill;
 use sp_runtime::traits::Get;
 use CoreAssignment::*;
 use CoretimeTraceItem::*;
@@ -32,7 +33,7 @@ use Finality::*;
 #[test]
 fn basic_initialize_works() {
 	TestExt::new().execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		assert_eq!(CoretimeTrace::get(), vec![]);
 		assert_eq!(Broker::current_timeslice(), 0);
 	});
@@ -41,7 +42,7 @@ fn basic_initialize_works() {
 #[test]
 fn drop_region_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_ok!(Broker::do_assign(region, Some(1), 1001, Provisional));
@@ -69,7 +70,7 @@ fn drop_region_works() {
 #[test]
 fn drop_renewal_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_ok!(Broker::do_assign(region, Some(1), 1001, Final));
@@ -86,7 +87,7 @@ fn drop_renewal_works() {
 #[test]
 fn drop_contribution_works() {
 	TestExt::new().contribution_timeout(3).endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		// Place region in pool. Active in pool timeslices 4, 5, 6 = rcblocks 8, 10, 12; we
@@ -109,7 +110,7 @@ fn drop_history_works() {
 		.endow(1, 1000)
 		.endow(2, 30)
 		.execute_with(|| {
-			assert_ok!(Broker::do_start_sales(100, 1));
+			assert_ok!(Broker::do_start_sales(100, 1, None));
 			advance_to(2);
 			let mut region = Broker::do_purchase(1, u64::max_value()).unwrap();
 			// Place region in pool. Active in pool timeslices 4, 5, 6 = rcblocks 8, 10, 12; we
@@ -168,7 +169,7 @@ fn drop_history_works() {
 #[test]
 fn request_core_count_works() {
 	TestExt::new().execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 0));
+		assert_ok!(Broker::do_start_sales(100, 0, None));
 		assert_ok!(Broker::request_core_count(RuntimeOrigin::root(), 1));
 		advance_to(12);
 		let assignment = vec![(Pool, 57600)];
@@ -182,7 +183,7 @@ fn request_core_count_works() {
 #[test]
 fn transfer_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_ok!(<Broker as Transfer<_>>::transfer(&region.into(), &2));
@@ -192,10 +193,52 @@ fn transfer_works() {
 	});
 }
 
+#[test]
+fn region_metadata_is_cleared_on_transfer() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let label: RegionMetadataOf<Test> = BoundedVec::truncate_from(b"staging".to_vec());
+		assert_noop!(
+			Broker::do_set_region_metadata(region, Some(2), label.clone()),
+			Error::<Test>::NotOwner
+		);
+		assert_ok!(Broker::do_set_region_metadata(region, Some(1), label.clone()));
+		System::assert_last_event(
+			Event::RegionMetadataSet { region_id: region, metadata: label.clone() }.into(),
+		);
+		assert_eq!(RegionMetadata::<Test>::get(region), Some(label.clone()));
+		assert_ok!(Broker::do_clear_region_metadata(region, Some(1)));
+		assert_eq!(RegionMetadata::<Test>::get(region), None);
+
+		assert_ok!(Broker::do_set_region_metadata(region, Some(1), label));
+		assert_ok!(Broker::do_transfer(region, Some(1), 2));
+		assert_eq!(RegionMetadata::<Test>::get(region), None);
+	});
+}
+
+#[test]
+fn partition_clears_metadata_and_listing() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let label: RegionMetadataOf<Test> = BoundedVec::truncate_from(b"staging".to_vec());
+		assert_ok!(Broker::do_set_region_metadata(region, Some(1), label));
+		assert_ok!(Broker::do_list_region(region, 1, 150));
+		let (region1, region2) = Broker::do_partition(region, Some(1), 2).unwrap();
+		for r in [region1, region2] {
+			assert_eq!(RegionMetadata::<Test>::get(r), None);
+			assert_eq!(Listings::<Test>::get(r), None);
+		}
+	});
+}
+
 #[test]
 fn permanent_is_not_reassignable() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_ok!(Broker::do_assign(region, Some(1), 1001, Final));
@@ -212,7 +255,7 @@ fn permanent_is_not_reassignable() {
 #[test]
 fn provisional_is_reassignable() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_ok!(Broker::do_assign(region, Some(1), 1001, Provisional));
@@ -252,7 +295,7 @@ fn provisional_is_reassignable() {
 #[test]
 fn nft_metadata_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_eq!(attribute::<Timeslice>(region, b"begin"), 4);
@@ -273,7 +316,7 @@ fn nft_metadata_works() {
 		assert_eq!(attribute::<u64>(region, b"owner"), 42);
 		assert_eq!(attribute::<CoreMask>(region, b"part"), 0x00000_fffff_fffff_00000.into());
 		assert_eq!(attribute::<CoreIndex>(region, b"core"), 0);
-		assert_eq!(attribute::<Option<u64>>(region, b"paid"), None);
+		assert_eq!(attribute::<Option<u64>>(region, b"paid"), Some(34));
 	});
 }
 
@@ -281,7 +324,7 @@ fn nft_metadata_works() {
 fn migration_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
 		assert_ok!(Broker::do_set_lease(1000, 8));
-		assert_ok!(Broker::do_start_sales(100, 2));
+		assert_ok!(Broker::do_start_sales(100, 2, None));
 
 		// Sale is for regions from TS4..7
 		// Not ending in this sale period.
@@ -314,7 +357,7 @@ fn migration_works() {
 #[test]
 fn renewal_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_eq!(balance(1), 900);
@@ -337,7 +380,7 @@ fn instapool_payouts_work() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
 		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
 		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
-		assert_ok!(Broker::do_start_sales(100, 3));
+		assert_ok!(Broker::do_start_sales(100, 3, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_ok!(Broker::do_pool(region, None, 2, Final));
@@ -347,18 +390,95 @@ fn instapool_payouts_work() {
 		advance_to(11);
 		assert_eq!(pot(), 14);
 		assert_eq!(revenue(), 106);
-		assert_ok!(Broker::do_claim_revenue(region, 100));
+		assert_ok!(Broker::do_claim_revenue(region, 100, None));
 		assert_eq!(pot(), 10);
 		assert_eq!(balance(2), 4);
 	});
 }
 
+#[test]
+fn anyone_can_claim_revenue_for_the_payee() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_start_sales(100, 3, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, None, 2, Final));
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		advance_to(8);
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 10));
+		advance_to(11);
+		// Account 3 neither owns the region nor is its payee.
+		assert_ok!(Broker::claim_revenue(RuntimeOrigin::signed(3), region, 100, None));
+		assert_eq!(balance(2), 4);
+		assert_eq!(balance(3), 0);
+		System::assert_has_event(Event::RevenueClaimPaid { who: 2, amount: 4, next: None }.into());
+	});
+}
+
+#[test]
+fn payee_can_claim_revenue_to_another_account() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_start_sales(100, 3, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, None, 2, Final));
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		advance_to(8);
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 10));
+		advance_to(11);
+		// Only the payee may redirect the revenue.
+		assert_noop!(
+			Broker::claim_revenue(RuntimeOrigin::signed(3), region, 100, Some(3)),
+			Error::<Test>::NotOwner
+		);
+		assert_ok!(Broker::claim_revenue(RuntimeOrigin::signed(2), region, 100, Some(5)));
+		assert_eq!(balance(2), 0);
+		assert_eq!(balance(5), 4);
+		System::assert_has_event(Event::RevenueClaimPaid { who: 5, amount: 4, next: None }.into());
+	});
+}
+
+#[test]
+fn revenue_can_be_compounded_into_credit() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_start_sales(100, 3, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, None, 2, Final));
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		advance_to(8);
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 10));
+		advance_to(11);
+		assert_eq!(pot(), 14);
+		// Only the payee may decide where the credit goes.
+		assert_noop!(Broker::do_compound_revenue(1, region, 100, 1), Error::<Test>::NotOwner);
+		assert_ok!(Broker::do_compound_revenue(2, region, 100, 2));
+		assert_eq!(balance(2), 0);
+		assert_eq!(CoretimeCredit::get().get(&2), Some(&4));
+		// The revenue stays with the pallet, now backing the credit.
+		assert_eq!(pot(), 14);
+		System::assert_last_event(
+			Event::RevenueCompounded { who: 2, beneficiary: 2, amount: 4, next: None }.into(),
+		);
+		assert_noop!(
+			Broker::do_compound_revenue(2, region, 100, 2),
+			Error::<Test>::UnknownContribution
+		);
+	});
+}
+
 #[test]
 fn instapool_partial_core_payouts_work() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
 		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
 		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
-		assert_ok!(Broker::do_start_sales(100, 2));
+		assert_ok!(Broker::do_start_sales(100, 2, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		let (region1, region2) =
@@ -369,8 +489,8 @@ fn instapool_partial_core_payouts_work() {
 		advance_to(8);
 		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 40));
 		advance_to(11);
-		assert_ok!(Broker::do_claim_revenue(region1, 100));
-		assert_ok!(Broker::do_claim_revenue(region2, 100));
+		assert_ok!(Broker::do_claim_revenue(region1, 100, None));
+		assert_ok!(Broker::do_claim_revenue(region2, 100, None));
 		assert_eq!(revenue(), 120);
 		assert_eq!(balance(2), 5);
 		assert_eq!(balance(3), 15);
@@ -378,6 +498,68 @@ fn instapool_partial_core_payouts_work() {
 	});
 }
 
+#[test]
+fn instapool_payouts_are_weighted_by_pooled_parts() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		// Half of the core goes to a task; the other half is pooled in a 10/25/65 split.
+		let (pooled, assigned) =
+			Broker::do_interlace(region, None, CoreMask::from_chunk(0, 40)).unwrap();
+		assert_ok!(Broker::do_assign(assigned, None, 1000, Final));
+		let (region1, rest) =
+			Broker::do_interlace(pooled, None, CoreMask::from_chunk(0, 4)).unwrap();
+		let (region2, region3) =
+			Broker::do_interlace(rest, None, CoreMask::from_chunk(4, 14)).unwrap();
+		assert_ok!(Broker::do_pool(region1, None, 2, Final));
+		assert_ok!(Broker::do_pool(region2, None, 3, Final));
+		assert_ok!(Broker::do_pool(region3, None, 4, Final));
+		assert_ok!(Broker::do_purchase_credit(1, 200, 1));
+		advance_to(8);
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 200));
+		advance_to(11);
+		assert_ok!(Broker::do_claim_revenue(region1, 100, None));
+		assert_ok!(Broker::do_claim_revenue(region2, 100, None));
+		assert_ok!(Broker::do_claim_revenue(region3, 100, None));
+		assert_eq!(revenue(), 100);
+		assert_eq!(balance(2), 20);
+		assert_eq!(balance(3), 50);
+		assert_eq!(balance(4), 130);
+		assert_eq!(pot(), 0);
+	});
+}
+
+#[test]
+fn instapool_payouts_do_not_saturate_with_large_revenue() {
+	TestExt::new().endow(1, u64::max_value()).execute_with(|| {
+		let revenue = u64::max_value() - 1000;
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let (pooled, assigned) =
+			Broker::do_interlace(region, None, CoreMask::from_chunk(0, 40)).unwrap();
+		assert_ok!(Broker::do_assign(assigned, None, 1000, Final));
+		let (region1, rest) =
+			Broker::do_interlace(pooled, None, CoreMask::from_chunk(0, 4)).unwrap();
+		let (region2, region3) =
+			Broker::do_interlace(rest, None, CoreMask::from_chunk(4, 14)).unwrap();
+		assert_ok!(Broker::do_pool(region1, None, 2, Final));
+		assert_ok!(Broker::do_pool(region2, None, 3, Final));
+		assert_ok!(Broker::do_pool(region3, None, 4, Final));
+		assert_ok!(Broker::do_purchase_credit(1, revenue, 1));
+		advance_to(8);
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, revenue));
+		advance_to(11);
+		assert_ok!(Broker::do_claim_revenue(region1, 100, None));
+		assert_ok!(Broker::do_claim_revenue(region2, 100, None));
+		assert_ok!(Broker::do_claim_revenue(region3, 100, None));
+		assert_eq!(balance(2), revenue / 10);
+		assert_eq!(balance(2) as u128 + balance(3) as u128 + balance(4) as u128, revenue as u128);
+		assert_eq!(pot(), 0);
+	});
+}
+
 #[test]
 fn initialize_with_system_paras_works() {
 	TestExt::new().execute_with(|| {
@@ -389,7 +571,7 @@ fn initialize_with_system_paras_works() {
 			ScheduleItem { assignment: Task(4u32), mask: 0x00000_00000_00000_fffff.into() },
 		];
 		assert_ok!(Broker::do_reserve(Schedule::truncate_from(items)));
-		assert_ok!(Broker::do_start_sales(100, 2));
+		assert_ok!(Broker::do_start_sales(100, 2, None));
 		advance_to(10);
 		assert_eq!(
 			CoretimeTrace::get(),
@@ -422,7 +604,7 @@ fn initialize_with_leased_slots_works() {
 	TestExt::new().execute_with(|| {
 		assert_ok!(Broker::do_set_lease(1000, 6));
 		assert_ok!(Broker::do_set_lease(1001, 7));
-		assert_ok!(Broker::do_start_sales(100, 2));
+		assert_ok!(Broker::do_start_sales(100, 2, None));
 		advance_to(18);
 		let end_hint = None;
 		assert_eq!(
@@ -466,7 +648,7 @@ fn initialize_with_leased_slots_works() {
 #[test]
 fn purchase_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_ok!(Broker::do_assign(region, None, 1000, Final));
@@ -486,10 +668,56 @@ fn purchase_works() {
 	});
 }
 
+#[test]
+fn purchase_core_works() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 3, None));
+		advance_to(2);
+		let region = Broker::do_purchase_core(1, 2, u64::max_value()).unwrap();
+		assert_eq!(region, RegionId { begin: 4, core: 2, mask: CoreMask::complete() });
+		assert_noop!(
+			Broker::do_purchase_core(1, 2, u64::max_value()),
+			Error::<Test>::CoreUnavailable
+		);
+		assert_noop!(
+			Broker::do_purchase_core(1, 5, u64::max_value()),
+			Error::<Test>::CoreUnavailable
+		);
+		// Unspecified purchases take the lowest-indexed core still free.
+		assert_eq!(Broker::do_purchase(1, u64::max_value()).unwrap().core, 0);
+		assert_noop!(
+			Broker::do_purchase_core(1, 0, u64::max_value()),
+			Error::<Test>::CoreUnavailable
+		);
+		assert_eq!(Broker::do_purchase(1, u64::max_value()).unwrap().core, 1);
+		assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::SoldOut);
+	});
+}
+
+#[test]
+fn cancel_sale_pools_cores_around_chosen_core() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 3, None));
+		advance_to(2);
+		assert_ok!(Broker::do_purchase_core(1, 1, u64::max_value()));
+		assert_ok!(Broker::do_cancel_sale(2));
+		System::assert_last_event(Event::SaleCancelled { cores_pooled: 2 }.into());
+		let just_pool = Schedule::truncate_from(vec![ScheduleItem {
+			assignment: Pool,
+			mask: CoreMask::complete(),
+		}]);
+		assert_eq!(Workplan::<Test>::get((4, 0)), Some(just_pool.clone()));
+		assert_eq!(Workplan::<Test>::get((4, 1)), None);
+		assert_eq!(Workplan::<Test>::get((4, 2)), Some(just_pool));
+		// The chosen core stays recorded so that renewals skip it.
+		assert_eq!(ChosenCores::<Test>::get().to_vec(), vec![1]);
+	});
+}
+
 #[test]
 fn partition_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		let (region1, region) = Broker::do_partition(region, None, 1).unwrap();
@@ -536,7 +764,7 @@ fn partition_works() {
 #[test]
 fn interlace_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		let (region1, region) =
@@ -562,10 +790,41 @@ fn interlace_works() {
 	});
 }
 
+#[test]
+fn interlace_with_striped_masks_works() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let evens = CoreMask::from_mask((0..80).step_by(2));
+		let (region1, region) = Broker::do_interlace(region, None, evens).unwrap();
+		assert_eq!(region.mask, !evens);
+		let every_fourth = CoreMask::from_mask((1..80).step_by(4));
+		let (region2, region3) = Broker::do_interlace(region, None, every_fourth).unwrap();
+		assert_eq!(region3.mask.count_ones(), 20);
+		assert_ok!(Broker::do_assign(region1, None, 1001, Final));
+		assert_ok!(Broker::do_assign(region2, None, 1002, Final));
+		assert_ok!(Broker::do_assign(region3, None, 1003, Final));
+		advance_to(10);
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![(
+				6,
+				AssignCore {
+					core: 0,
+					begin: 8,
+					assignment: vec![(Task(1001), 28800), (Task(1002), 14400), (Task(1003), 14400),],
+					end_hint: None
+				}
+			),]
+		);
+	});
+}
+
 #[test]
 fn interlace_then_partition_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		let (region1, region2) =
@@ -615,7 +874,7 @@ fn interlace_then_partition_works() {
 #[test]
 fn partition_then_interlace_works() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		let (region1, region2) = Broker::do_partition(region, None, 1).unwrap();
@@ -742,7 +1001,7 @@ fn purchase_requires_valid_status_and_sale_info() {
 		SaleInfo::<Test>::put(&dummy_sale);
 		assert_noop!(Broker::do_purchase(1, 100), Error::<Test>::SoldOut);
 
-		assert_ok!(Broker::do_start_sales(200, 1));
+		assert_ok!(Broker::do_start_sales(200, 1, None));
 		assert_noop!(Broker::do_purchase(1, 100), Error::<Test>::TooEarly);
 
 		advance_to(2);
@@ -784,7 +1043,7 @@ fn renewal_requires_valid_status_and_sale_info() {
 		SaleInfo::<Test>::put(&dummy_sale);
 		assert_noop!(Broker::do_renew(1, 1), Error::<Test>::SoldOut);
 
-		assert_ok!(Broker::do_start_sales(200, 1));
+		assert_ok!(Broker::do_start_sales(200, 1, None));
 		assert_noop!(Broker::do_renew(1, 1), Error::<Test>::NotAllowed);
 
 		let record = AllowedRenewalRecord {
@@ -812,7 +1071,7 @@ fn cannot_transfer_or_partition_or_interlace_unknown() {
 #[test]
 fn check_ownership_for_transfer_or_partition_or_interlace() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_noop!(Broker::do_transfer(region, Some(2), 2), Error::<Test>::NotOwner);
@@ -827,7 +1086,7 @@ fn check_ownership_for_transfer_or_partition_or_interlace() {
 #[test]
 fn cannot_partition_invalid_offset() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		assert_noop!(Broker::do_partition(region, None, 0), Error::<Test>::PivotTooEarly);
@@ -835,10 +1094,115 @@ fn cannot_partition_invalid_offset() {
 	});
 }
 
+#[test]
+fn schedule_for_core_projects_leases_and_purchases() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_eq!(Broker::schedule_for_core(0, 3), vec![]);
+		assert_ok!(Broker::do_set_lease(1000, 8));
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_eq!(region.core, 1);
+		assert_ok!(Broker::do_assign(region, None, 2000, Final));
+
+		let lease = vec![(Task(1000), 57600)];
+		let purchase = vec![(Task(2000), 57600)];
+		// The lease is placed on the first core until its last cycle, after which the core is
+		// expected to keep its final assignment.
+		assert_eq!(
+			Broker::schedule_for_core(0, 3),
+			vec![(4, lease.clone()), (7, lease.clone()), (10, lease.clone())]
+		);
+		assert_eq!(Broker::schedule_for_core(1, 2), vec![(4, purchase.clone()), (7, purchase)]);
+
+		// The projection matches what is actually given to the Relay-chain.
+		advance_to(8);
+		assert_eq!(Broker::effective_assignment(0, 7), lease);
+	});
+}
+
+#[test]
+fn partition_splits_paid_by_timeslice() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(90, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_eq!(balance(1), 910);
+		assert_eq!(Regions::<Test>::get(&region).unwrap().paid, Some(90));
+
+		let (region1, region2) = Broker::do_partition(region, None, 1).unwrap();
+		assert_eq!(Regions::<Test>::get(&region1).unwrap().paid, Some(30));
+		assert_eq!(Regions::<Test>::get(&region2).unwrap().paid, Some(60));
+		let (region2, region3) = Broker::do_partition(region2, None, 1).unwrap();
+		assert_eq!(Regions::<Test>::get(&region2).unwrap().paid, Some(30));
+		assert_eq!(Regions::<Test>::get(&region3).unwrap().paid, Some(30));
+
+		// Interlacing does not change the length, so both parts keep what was paid.
+		let (region4, region5) =
+			Broker::do_interlace(region3, None, CoreMask::from_chunk(0, 40)).unwrap();
+		assert_eq!(Regions::<Test>::get(&region4).unwrap().paid, Some(30));
+		assert_eq!(Regions::<Test>::get(&region5).unwrap().paid, Some(30));
+	});
+}
+
+#[test]
+fn partition_by_fraction_works() {
+	TestExt::new().endow(1, 1000).region_length(5).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let length = |id: RegionId| Regions::<Test>::get(&id).unwrap().end - id.begin;
+		assert_eq!(length(region), 5);
+		// Fractions leaving one side empty are rejected.
+		assert_noop!(
+			Broker::do_partition_fraction(region, None, Perbill::from_percent(5)),
+			Error::<Test>::PivotTooEarly
+		);
+		assert_noop!(
+			Broker::do_partition_fraction(region, None, Perbill::from_percent(95)),
+			Error::<Test>::PivotTooLate
+		);
+		assert_noop!(
+			Broker::do_partition_fraction(region, Some(2), Perbill::from_percent(50)),
+			Error::<Test>::NotOwner
+		);
+
+		let (region1, region2) =
+			Broker::do_partition_fraction(region, None, Perbill::from_percent(50)).unwrap();
+		assert_eq!((length(region1), length(region2)), (2, 3));
+		assert_eq!(region2.begin, region.begin + 2);
+		let (region3, region4) =
+			Broker::do_partition_fraction(region2, None, Perbill::from_percent(50)).unwrap();
+		assert_eq!((length(region3), length(region4)), (1, 2));
+	});
+}
+
+#[test]
+fn cannot_partition_at_region_boundaries() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let end = Regions::<Test>::get(&region).unwrap().end;
+		assert_eq!((region.begin, end), (4, 7));
+		// Pivoting on either end of the region would leave one side empty.
+		assert_noop!(Broker::do_partition(region, None, 0), Error::<Test>::PivotTooEarly);
+		assert_noop!(Broker::do_partition(region, None, 3), Error::<Test>::PivotTooLate);
+
+		let (region1, region2) = Broker::do_partition(region, None, 1).unwrap();
+		assert_noop!(Broker::do_partition(region1, None, 1), Error::<Test>::PivotTooLate);
+		assert_noop!(Broker::do_partition(region2, None, 0), Error::<Test>::PivotTooEarly);
+		assert_noop!(Broker::do_partition(region2, None, 2), Error::<Test>::PivotTooLate);
+		assert_ok!(Broker::do_partition(region2, None, 1));
+		// No region without any coverage has been created.
+		assert!(Regions::<Test>::iter().all(|(id, r)| id.begin < r.end));
+	});
+}
+
 #[test]
 fn cannot_interlace_invalid_pivot() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		let (region1, _) = Broker::do_interlace(region, None, CoreMask::from_chunk(0, 20)).unwrap();
@@ -857,10 +1221,30 @@ fn cannot_interlace_invalid_pivot() {
 	});
 }
 
+#[test]
+fn cannot_interlace_whole_core_with_degenerate_pivot() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert!(region.mask.is_complete());
+		assert_noop!(
+			Broker::do_interlace(region, None, CoreMask::complete()),
+			Error::<Test>::CompletePivot
+		);
+		assert_noop!(
+			Broker::do_interlace(region, None, CoreMask::void()),
+			Error::<Test>::VoidPivot
+		);
+		// No region without any coverage has been created.
+		assert!(Regions::<Test>::iter_keys().all(|id| !id.mask.is_void()));
+	});
+}
+
 #[test]
 fn assign_should_drop_invalid_region() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let mut region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		advance_to(10);
@@ -873,7 +1257,7 @@ fn assign_should_drop_invalid_region() {
 #[test]
 fn pool_should_drop_invalid_region() {
 	TestExt::new().endow(1, 1000).execute_with(|| {
-		assert_ok!(Broker::do_start_sales(100, 1));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
 		advance_to(2);
 		let mut region = Broker::do_purchase(1, u64::max_value()).unwrap();
 		advance_to(10);
@@ -894,3 +1278,1295 @@ fn config_works() {
 		assert_noop!(Broker::configure(Root.into(), cfg), Error::<Test>::InvalidConfig);
 	});
 }
+
+#[test]
+fn region_on_removed_core_is_unassignable() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		assert_ok!(Broker::do_purchase(1, u64::max_value()));
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_eq!(region.core, 1);
+		assert_ok!(Broker::request_core_count(RuntimeOrigin::root(), 1));
+		advance_to(3);
+		assert_eq!(Status::<Test>::get().unwrap().core_count, 1);
+		assert_noop!(
+			Broker::do_assign(region, Some(1), 1001, Final),
+			Error::<Test>::CoreNoLongerExists
+		);
+		assert_noop!(Broker::do_pool(region, Some(1), 1, Final), Error::<Test>::CoreNoLongerExists);
+		// The Region itself is still around and may be dropped once it expires.
+		assert!(Regions::<Test>::get(region).is_some());
+	});
+}
+
+#[test]
+fn region_on_removed_core_is_refundable() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let kept = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let paid = Regions::<Test>::get(region).unwrap().paid.unwrap();
+		assert_noop!(Broker::do_claim_refund(region, Some(1)), Error::<Test>::NotRefundable);
+		assert_ok!(Broker::request_core_count(RuntimeOrigin::root(), 1));
+		advance_to(3);
+
+		assert_noop!(Broker::do_claim_refund(kept, Some(1)), Error::<Test>::NotRefundable);
+		assert_noop!(Broker::do_claim_refund(region, Some(2)), Error::<Test>::NotOwner);
+		let before = balance(1);
+		assert_ok!(Broker::claim_refund(RuntimeOrigin::signed(1), region));
+		assert_eq!(balance(1), before + paid);
+		assert_eq!(Regions::<Test>::get(region), None);
+		System::assert_last_event(
+			Event::RegionRefunded { region_id: region, who: 1, amount: paid }.into(),
+		);
+	});
+}
+
+#[test]
+fn purchase_guard_trips_when_front_run() {
+	TestExt::new().endow(1, 1000).endow(2, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		// Account 2 wants two cores to still be on sale, but account 1 gets in first.
+		assert_ok!(Broker::do_purchase(1, u64::max_value()));
+		assert_noop!(
+			Broker::do_purchase_guarded(2, u64::max_value(), 2),
+			Error::<Test>::CoresExhausted
+		);
+		assert_eq!(balance(2), 1000);
+		assert_ok!(Broker::do_purchase_guarded(2, u64::max_value(), 1));
+		assert_noop!(
+			Broker::do_purchase_guarded(2, u64::max_value(), 1),
+			Error::<Test>::CoresExhausted
+		);
+	});
+}
+
+#[test]
+fn effective_assignment_matches_committed_assignment() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let (region1, region2) = Broker::do_partition(region, None, 1).unwrap();
+		let (region1, region3) =
+			Broker::do_interlace(region1, None, CoreMask::from_chunk(0, 20)).unwrap();
+		assert_ok!(Broker::do_assign(region1, None, 1001, Final));
+		assert_ok!(Broker::do_assign(region2, None, 1002, Final));
+		assert_ok!(Broker::do_assign(region3, None, 1003, Final));
+		let expected_4 = Broker::effective_assignment(0, 4);
+		let expected_5 = Broker::effective_assignment(0, 5);
+		assert_eq!(expected_4, vec![(Task(1001), 14400), (Task(1003), 43200)]);
+		assert_eq!(expected_5, vec![(Task(1002), 57600)]);
+		advance_to(10);
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![
+				(6, AssignCore { core: 0, begin: 8, assignment: expected_4, end_hint: None }),
+				(8, AssignCore { core: 0, begin: 10, assignment: expected_5, end_hint: None }),
+			]
+		);
+		// Nothing changes once the workplans have been committed.
+		assert_eq!(Broker::effective_assignment(0, 5), vec![(Task(1002), 57600)]);
+	});
+}
+
+#[test]
+fn renew_pooled_works() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, Some(1), 2, Final));
+		advance_to(6);
+		// The pooled core may not be renewed without accounting for the contribution.
+		assert_noop!(Broker::do_renew(1, region.core), Error::<Test>::NotAllowed);
+		let renewed = Broker::do_renew_pooled(1, region).unwrap();
+		assert_eq!(balance(1), 800);
+		assert_eq!(renewed, RegionId { begin: 7, core: 0, mask: CoreMask::complete() });
+		assert_eq!(
+			InstaPoolContribution::<Test>::get(renewed),
+			Some(ContributionRecord { length: 3, payee: 2 })
+		);
+		assert_eq!(InstaPoolIo::<Test>::get(7).private, 80);
+		assert_eq!(InstaPoolIo::<Test>::get(10).private, -80);
+		System::assert_has_event(Event::<Test>::Pooled { region_id: renewed, duration: 3 }.into());
+		// The original contribution is untouched and can still be claimed.
+		assert!(InstaPoolContribution::<Test>::get(region).is_some());
+		// And the renewed Region can itself be renewed in the next sale.
+		advance_to(12);
+		assert_ok!(Broker::do_renew_pooled(1, renewed));
+	});
+}
+
+#[test]
+fn price_ema_tracks_concluded_sales() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		assert_eq!(PriceEma::<Test>::get(), None);
+		advance_to(2);
+		assert_ok!(Broker::do_purchase(1, u64::max_value()));
+		advance_to(6);
+		// The first sale sold out at 100.
+		assert_eq!(PriceEma::<Test>::get(), Some(100));
+		advance_to(8);
+		assert_ok!(Broker::do_purchase(1, u64::max_value()));
+		assert_eq!(balance(1), 700);
+		advance_to(12);
+		// The second sold out at 200.
+		assert_eq!(PriceEma::<Test>::get(), Some(150));
+		advance_to(18);
+		// The third sold nothing at its regular price of 400.
+		let ema = PriceEma::<Test>::get().unwrap();
+		assert_eq!(ema, 275);
+		assert!(ema > 100 && ema < 400);
+	});
+}
+
+#[test]
+fn purchase_with_asset_works() {
+	TestExt::new().endow_asset(1, 1, 500).endow_asset(2, 1, 500).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		assert_noop!(
+			Broker::do_purchase_with(1, u64::max_value(), Some(2)),
+			Error::<Test>::UnsupportedAsset
+		);
+		assert_noop!(Broker::do_purchase_with(1, 99, Some(1)), Error::<Test>::Overpriced);
+		let region = Broker::do_purchase_with(1, 100, Some(1)).unwrap();
+		// The price of 100 is taken as 200 of asset 1, which goes to `OnAssetRevenue`.
+		assert_eq!(asset_balance(1, 1), 300);
+		assert_eq!(asset_balance(1, Broker::account_id()), 0);
+		assert_eq!(asset_balance(1, 0), 200);
+		assert_eq!(asset_balance(2, 1), 500);
+		assert_eq!(balance(1), 0);
+		assert_eq!(revenue(), 0);
+		assert_eq!(Regions::<Test>::get(&region).unwrap().paid, Some(100));
+		// Paying natively is unaffected.
+		assert!(Broker::do_purchase(1, u64::max_value()).is_err());
+	});
+}
+
+#[test]
+fn sale_price_is_floored_by_min_price() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		MinPrice::set(250);
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		assert_noop!(Broker::do_purchase(1, 249), Error::<Test>::Overpriced);
+		assert_ok!(Broker::do_purchase(1, 250));
+		assert_eq!(balance(1), 750);
+	});
+}
+
+#[test]
+fn cancel_sale_pools_unsold_cores() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		assert_noop!(Broker::do_cancel_sale(1), Error::<Test>::TooManyUnsoldCores);
+		assert_eq!(Broker::do_cancel_sale(2), Ok(2));
+		System::assert_last_event(Event::SaleCancelled { cores_pooled: 2 }.into());
+		let just_pool = Schedule::truncate_from(vec![ScheduleItem {
+			assignment: Pool,
+			mask: CoreMask::complete(),
+		}]);
+		assert_eq!(Workplan::<Test>::get((4, 0)), Some(just_pool.clone()));
+		assert_eq!(Workplan::<Test>::get((4, 1)), Some(just_pool));
+		assert_eq!(InstaPoolIo::<Test>::get(4).system, 160);
+		assert_eq!(InstaPoolIo::<Test>::get(7).system, -160);
+		assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::SoldOut);
+		// The rotation of the cancelled sale does not pool the cores a second time.
+		advance_to(6);
+		assert_eq!(Status::<Test>::get().unwrap().system_pool_size, 160);
+	});
+}
+
+#[test]
+fn renewal_works_after_cancelled_sale() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_assign(region, None, 1001, Final));
+		advance_to(6);
+		let pooled_before = InstaPoolIo::<Test>::get(7).system;
+		assert_ok!(Broker::do_cancel_sale(2));
+		assert_eq!(InstaPoolIo::<Test>::get(7).system, pooled_before + 160);
+		assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::SoldOut);
+		assert_eq!(Broker::sale_status(), SaleStatus::SoldOut);
+
+		let core = Broker::do_renew(1, region.core).unwrap();
+		let task = Schedule::truncate_from(vec![ScheduleItem {
+			assignment: Task(1001),
+			mask: CoreMask::complete(),
+		}]);
+		assert_eq!(Workplan::<Test>::get((7, core)), Some(task));
+		// Only the other core remains in the pool.
+		assert_eq!(InstaPoolIo::<Test>::get(7).system, pooled_before + 80);
+		assert_eq!(InstaPoolIo::<Test>::get(10).system, -80);
+		assert_noop!(Broker::do_cancel_sale(2), Error::<Test>::SoldOut);
+
+		// The rotation neither pools the cores again nor carries the cancellation over.
+		advance_to(12);
+		assert_eq!(Status::<Test>::get().unwrap().system_pool_size, 80);
+		assert_eq!(CancelledSale::<Test>::get(), None);
+		assert_ok!(Broker::do_renew(1, core));
+	});
+}
+
+#[test]
+fn assign_multi_works() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let assignments = WeightedAssignments::truncate_from(vec![(1001, 60), (1002, 40)]);
+		assert_ok!(Broker::do_assign_multi(region, None, assignments, Final));
+		advance_to(6);
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![(
+				6,
+				AssignCore {
+					core: 0,
+					begin: 8,
+					assignment: vec![(Task(1001), 34560), (Task(1002), 23040)],
+					end_hint: None
+				}
+			),]
+		);
+	});
+}
+
+#[test]
+fn assign_multi_requires_some_weight() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let assignments = WeightedAssignments::truncate_from(vec![(1001, 0), (1002, 0)]);
+		assert_noop!(
+			Broker::do_assign_multi(region, None, assignments, Final),
+			Error::<Test>::ZeroAssignmentWeight
+		);
+		assert_noop!(
+			Broker::do_assign_multi(region, None, Default::default(), Final),
+			Error::<Test>::ZeroAssignmentWeight
+		);
+	});
+}
+
+#[test]
+fn timeslice_period_and_region_length_shift_sales_and_assignments() {
+	TestExt::new()
+		.timeslice_period(4)
+		.region_length(5)
+		.endow(1, 1000)
+		.execute_with(|| {
+			assert_ok!(Broker::do_start_sales(100, 1, None));
+			let sale = SaleInfo::<Test>::get().unwrap();
+			assert_eq!((sale.region_begin, sale.region_end), (5, 10));
+			advance_to(2);
+			let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+			assert_eq!(region.begin, 5);
+			assert_eq!(Regions::<Test>::get(region).unwrap().end, 10);
+			assert_ok!(Broker::do_assign(region, None, 1001, Final));
+			// Timeslice 5 begins at relay block 20 and is committed `advance_notice` blocks before.
+			advance_to(17);
+			assert!(CoretimeTrace::get().is_empty());
+			advance_to(18);
+			assert_eq!(
+				CoretimeTrace::get(),
+				vec![(
+					18,
+					AssignCore {
+						core: 0,
+						begin: 20,
+						assignment: vec![(Task(1001), 57600)],
+						end_hint: None
+					}
+				),]
+			);
+			let sale = SaleInfo::<Test>::get().unwrap();
+			assert_eq!((sale.region_begin, sale.region_end), (10, 15));
+		});
+}
+
+#[test]
+fn list_and_buy_region_works() {
+	TestExt::new().endow(1, 1000).endow(2, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_noop!(Broker::do_list_region(region, 2, 150), Error::<Test>::NotOwner);
+		assert_noop!(Broker::do_buy_listed(2, region, 150), Error::<Test>::NotListed);
+		assert_ok!(Broker::do_list_region(region, 1, 150));
+		assert_eq!(Listings::<Test>::get(region), Some((1, 150)));
+
+		assert_noop!(Broker::do_buy_listed(2, region, 149), Error::<Test>::Overpriced);
+		assert_ok!(Broker::do_buy_listed(2, region, 150));
+		assert_eq!(balance(1), 1050);
+		assert_eq!(balance(2), 850);
+		assert_eq!(Regions::<Test>::get(region).unwrap().owner, 2);
+		assert_eq!(Listings::<Test>::get(region), None);
+		System::assert_last_event(
+			Event::ListedRegionSold { region_id: region, seller: 1, buyer: 2, price: 150 }.into(),
+		);
+		assert_noop!(Broker::do_buy_listed(1, region, 150), Error::<Test>::NotListed);
+	});
+}
+
+#[test]
+fn unlist_region_works() {
+	TestExt::new().endow(1, 1000).endow(2, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_list_region(region, 1, 150));
+		assert_noop!(Broker::do_unlist_region(region, Some(2)), Error::<Test>::NotOwner);
+		assert_ok!(Broker::do_unlist_region(region, Some(1)));
+		assert_eq!(Listings::<Test>::get(region), None);
+		assert_noop!(Broker::do_buy_listed(2, region, 150), Error::<Test>::NotListed);
+		assert_noop!(Broker::do_unlist_region(region, Some(1)), Error::<Test>::NotListed);
+
+		// Listings do not survive the Region changing hands.
+		assert_ok!(Broker::do_list_region(region, 1, 150));
+		assert_ok!(Broker::do_transfer(region, Some(1), 2));
+		assert_noop!(Broker::do_buy_listed(2, region, 150), Error::<Test>::NotListed);
+	});
+}
+
+#[test]
+fn place_order_pays_pool_contributors() {
+	TestExt::new().endow(1, 1000).endow(3, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		assert_noop!(Broker::do_place_order(3, 1001, u64::max_value()), Error::<Test>::Unavailable);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, None, 2, Final));
+		advance_to(8);
+		let price = Broker::spot_price().unwrap();
+		assert!(price > 0);
+		assert_noop!(Broker::do_place_order(3, 1001, price - 1), Error::<Test>::Overpriced);
+		assert_ok!(Broker::do_place_order(3, 1001, price));
+		System::assert_last_event(Event::OrderPlaced { who: 3, task: 1001, when: 4, price }.into());
+		assert_eq!(balance(3), 1000 - price);
+		assert_eq!(pot(), price);
+		assert_eq!(CoretimeOrders::get(), vec![(9, 1001, price)]);
+		advance_to(11);
+		assert_ok!(Broker::do_claim_revenue(region, 100, None));
+		assert_eq!(balance(2), price);
+		assert_eq!(pot(), 0);
+	});
+}
+
+#[test]
+fn unspent_credit_expires() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		CreditExpiry::set(5);
+		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		assert_eq!(CreditExpiries::<Test>::get(6, 1), 20);
+		advance_to(11);
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&20));
+		advance_to(12);
+		System::assert_has_event(Event::CreditExpired { who: 1, amount: 20 }.into());
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&0));
+		assert_eq!(CreditExpiries::<Test>::get(6, 1), 0);
+		assert_eq!(pot(), 0);
+		assert_eq!(revenue(), 20);
+		assert_eq!(TestCoretimeProvider::spend_instantaneous(1, 1), Err(()));
+	});
+}
+
+#[test]
+fn credit_expiry_spares_later_grants() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		CreditExpiry::set(5);
+		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		advance_to(8);
+		// Spending comes out of the first grant, leaving 5 of it when the second is made.
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 15));
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		assert_eq!(CreditOutstanding::<Test>::get(1), 40);
+
+		advance_to(12);
+		System::assert_has_event(Event::CreditExpired { who: 1, amount: 5 }.into());
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&20));
+		assert_eq!(CreditOutstanding::<Test>::get(1), 20);
+
+		advance_to(18);
+		System::assert_has_event(Event::CreditExpired { who: 1, amount: 20 }.into());
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&0));
+		assert!(!CreditOutstanding::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn transferred_credit_leaves_later_grants_to_expire_alone() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		CreditExpiry::set(5);
+		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		// The whole of the first grant moves to 2, so it leaves nothing of its own to expire.
+		assert_ok!(Broker::do_transfer_credit(1, 2, 20));
+		advance_to(8);
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+
+		advance_to(12);
+		System::assert_has_event(Event::CreditExpired { who: 1, amount: 0 }.into());
+		System::assert_has_event(Event::CreditExpired { who: 2, amount: 20 }.into());
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&20));
+		assert_eq!(CoretimeCredit::get().get(&2), Some(&0));
+		assert_eq!(CreditOutstanding::<Test>::get(1), 20);
+
+		advance_to(18);
+		System::assert_has_event(Event::CreditExpired { who: 1, amount: 20 }.into());
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&0));
+		assert!(!CreditOutstanding::<Test>::contains_key(1));
+		assert!(!CreditOutstanding::<Test>::contains_key(2));
+	});
+}
+
+#[test]
+fn credit_can_be_transferred() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		let item = ScheduleItem { assignment: Pool, mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		assert_ok!(Broker::do_purchase_credit(1, 40, 1));
+		assert_ok!(Broker::do_transfer_credit(1, 2, 15));
+		System::assert_last_event(Event::CreditTransferred { from: 1, to: 2, amount: 15 }.into());
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&25));
+		assert_eq!(CoretimeCredit::get().get(&2), Some(&15));
+		assert_eq!(CreditExpiries::<Test>::get(1_001, 2), 15);
+		// No more than the unspent credit may be transferred.
+		assert_noop!(Broker::do_transfer_credit(1, 2, 26), Error::<Test>::InsufficientCredit);
+		assert_eq!(CoretimeCredit::get().get(&1), Some(&25));
+
+		advance_to(8);
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 25));
+		assert_ok!(TestCoretimeProvider::spend_instantaneous(2, 15));
+		assert_eq!(TestCoretimeProvider::spend_instantaneous(2, 1), Err(()));
+	});
+}
+
+#[test]
+fn set_reservation_takes_effect_from_next_sale() {
+	TestExt::new().execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let item = ScheduleItem { assignment: Task(1000), mask: CoreMask::complete() };
+		let schedule = Schedule::truncate_from(vec![item]);
+		assert_noop!(
+			Broker::do_set_reservation(1, schedule.clone()),
+			Error::<Test>::UnknownReservation
+		);
+		assert_ok!(Broker::do_set_reservation(0, schedule.clone()));
+		assert_eq!(Reservations::<Test>::get(), vec![schedule.clone()]);
+		// The ongoing sale is unaffected.
+		assert_eq!(SaleInfo::<Test>::get().unwrap().first_core, 0);
+		assert_eq!(SaleInfo::<Test>::get().unwrap().cores_offered, 2);
+
+		advance_to(6);
+		let sale = SaleInfo::<Test>::get().unwrap();
+		assert_eq!((sale.first_core, sale.cores_offered), (1, 1));
+		advance_to(12);
+		assert!(CoretimeTrace::get().contains(&(
+			12,
+			AssignCore {
+				core: 0,
+				begin: 14,
+				assignment: vec![(Task(1000), 57600)],
+				end_hint: None
+			}
+		)));
+
+		// Overwriting is fine, but there are only two cores to go around.
+		let item = ScheduleItem { assignment: Task(1001), mask: CoreMask::complete() };
+		assert_ok!(Broker::do_set_reservation(0, Schedule::truncate_from(vec![item])));
+		assert_ok!(Broker::do_set_reservation(1, schedule.clone()));
+		assert_noop!(Broker::do_set_reservation(2, schedule), Error::<Test>::TooManyReservations);
+	});
+}
+
+#[test]
+fn set_reservation_replacement_is_validated() {
+	TestExt::new().execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let item = ScheduleItem { assignment: Task(1000), mask: CoreMask::complete() };
+		let first = Schedule::truncate_from(vec![item]);
+		let item = ScheduleItem { assignment: Task(1001), mask: CoreMask::complete() };
+		let second = Schedule::truncate_from(vec![item]);
+		assert_ok!(Broker::do_reserve(first.clone()));
+		assert_ok!(Broker::do_set_reservation(0, second.clone()));
+		assert_eq!(ReserveCalls::get(), vec![(0, first.clone()), (0, second.clone())]);
+
+		// The hook may refuse a replacement.
+		ReservationBudget::set(Some(0));
+		assert_noop!(
+			Broker::do_set_reservation(0, first.clone()),
+			sp_runtime::DispatchError::Other("Reservation budget exhausted")
+		);
+		ReservationBudget::set(None);
+
+		// A replacement may not be made once the reservations exceed the cores.
+		assert_ok!(Broker::do_set_lease(2000, 100));
+		assert_ok!(Broker::request_core_count(RuntimeOrigin::root(), 1));
+		advance_to(3);
+		assert_noop!(Broker::do_set_reservation(0, first), Error::<Test>::TooManyReservations);
+		assert_eq!(Reservations::<Test>::get(), vec![second]);
+	});
+}
+
+#[test]
+fn sale_index_and_boundaries_work() {
+	TestExt::new().execute_with(|| {
+		assert_eq!(SaleIndex::<Test>::get(), 0);
+		assert_eq!(Broker::next_sale_rotation(), None);
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		for (index, region_begin, rotation) in [(1, 4, 6), (2, 7, 12), (3, 10, 18)] {
+			assert_eq!(SaleIndex::<Test>::get(), index);
+			assert_eq!(SaleInfo::<Test>::get().unwrap().region_begin, region_begin);
+			assert_eq!(Broker::next_sale_rotation(), Some(rotation));
+			advance_to(rotation as u64 - 1);
+			assert_eq!(SaleIndex::<Test>::get(), index);
+			advance_to(rotation as u64);
+		}
+		assert_eq!(SaleIndex::<Test>::get(), 4);
+	});
+}
+
+#[test]
+fn purchase_part_works() {
+	TestExt::new().endow(1, 1000).endow(2, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		assert_noop!(
+			Broker::do_purchase_part(1, CoreMask::void(), u64::max_value()),
+			Error::<Test>::VoidPart
+		);
+		// A fifth of the core costs a fifth of the price.
+		let part = CoreMask::from_chunk(0, 16);
+		let region = Broker::do_purchase_part(1, part, u64::max_value()).unwrap();
+		assert_eq!(region, RegionId { begin: 4, core: 0, mask: part });
+		assert_eq!(balance(1), 980);
+		assert_noop!(
+			Broker::do_purchase_part(2, CoreMask::from_chunk(8, 24), u64::max_value()),
+			Error::<Test>::PartUnavailable
+		);
+		// The core counts as sold, so no whole core remains.
+		assert_noop!(Broker::do_purchase(2, u64::max_value()), Error::<Test>::SoldOut);
+		let rest = CoreMask::from_chunk(16, 80);
+		assert_noop!(Broker::do_purchase_part(2, rest, 79), Error::<Test>::Overpriced);
+		let region = Broker::do_purchase_part(2, rest, u64::max_value()).unwrap();
+		assert_eq!(region, RegionId { begin: 4, core: 0, mask: rest });
+		assert_eq!(balance(2), 920);
+		assert_noop!(
+			Broker::do_purchase_part(2, CoreMask::from_chunk(0, 16), u64::max_value()),
+			Error::<Test>::SoldOut
+		);
+	});
+}
+
+#[test]
+fn unsold_part_is_pooled_on_rotation() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region =
+			Broker::do_purchase_part(1, CoreMask::from_chunk(0, 16), u64::max_value()).unwrap();
+		assert_ok!(Broker::do_assign(region, None, 1001, Final));
+		advance_to(6);
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![(
+				6,
+				AssignCore {
+					core: 0,
+					begin: 8,
+					assignment: vec![(Pool, 46080), (Task(1001), 11520)],
+					end_hint: None
+				}
+			)]
+		);
+	});
+}
+
+#[test]
+fn region_deposits_are_held_and_released() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		RegionDeposit::set(5);
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_eq!(balance(1), 895);
+		assert_eq!(held(1), 5);
+		assert_eq!(pot(), 0);
+		let (region1, region2) = Broker::do_partition(region, None, 2).unwrap();
+		assert_eq!(held(1), 10);
+		let (region2, region3) =
+			Broker::do_interlace(region2, None, CoreMask::from_chunk(0, 40)).unwrap();
+		assert_eq!(balance(1), 885);
+		assert_eq!(held(1), 15);
+		assert_eq!(RegionDeposits::<Test>::iter().count(), 3);
+
+		// Assigning a Region finally removes it, and with it the deposit.
+		assert_ok!(Broker::do_assign(region1, None, 1001, Final));
+		assert_eq!(balance(1), 890);
+		assert_eq!(held(1), 10);
+
+		advance_to(12);
+		assert_ok!(Broker::do_drop_region(region2));
+		assert_ok!(Broker::do_drop_region(region3));
+		assert_eq!(balance(1), 900);
+		assert_eq!(held(1), 0);
+		assert_eq!(pot(), 0);
+		assert_eq!(RegionDeposits::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn unsold_cores_are_auto_pooled() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_assign(region, None, 1001, Final));
+		advance_to(6);
+		let unsold = RegionId { begin: 4, core: 1, mask: CoreMask::complete() };
+		System::assert_has_event(
+			Event::<Test>::UnsoldAutoPooled { region_id: unsold, duration: 3 }.into(),
+		);
+		let just_1001 = vec![(Task(1001), 57600)];
+		let just_pool = vec![(Pool, 57600)];
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![
+				(6, AssignCore { core: 0, begin: 8, assignment: just_1001, end_hint: None }),
+				(6, AssignCore { core: 1, begin: 8, assignment: just_pool, end_hint: None }),
+			]
+		);
+		assert_eq!(Status::<Test>::get().unwrap().system_pool_size, 80);
+	});
+}
+
+#[test]
+fn unsold_cores_stay_idle_without_auto_pool() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		AutoPoolUnsold::set(false);
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_assign(region, None, 1001, Final));
+		advance_to(6);
+		assert!(!System::events()
+			.iter()
+			.any(|e| matches!(e.event, RuntimeEvent::Broker(Event::UnsoldAutoPooled { .. }))));
+		let just_1001 = vec![(Task(1001), 57600)];
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![(6, AssignCore { core: 0, begin: 8, assignment: just_1001, end_hint: None })]
+		);
+		assert_eq!(Status::<Test>::get().unwrap().system_pool_size, 0);
+	});
+}
+
+#[test]
+fn set_timeslice_matches_advance_to() {
+	let run = |jump: fn(Timeslice)| {
+		TestExt::new().endow(1, 1000).execute_with(|| {
+			CreditExpiry::set(5);
+			assert_ok!(Broker::do_start_sales(100, 2, None));
+			jump(1);
+			let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+			assert_ok!(Broker::do_pool(region, None, 2, Final));
+			assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+			jump(5);
+			assert_ok!(TestCoretimeProvider::spend_instantaneous(1, 10));
+			jump(13);
+			(
+				System::block_number(),
+				Status::<Test>::get(),
+				SaleInfo::<Test>::get(),
+				InstaPoolHistory::<Test>::iter().collect::<Vec<_>>(),
+				Workload::<Test>::iter().collect::<Vec<_>>(),
+				CoretimeTrace::get(),
+				pot(),
+			)
+		})
+	};
+	let stepped = run(|ts| advance_to((ts * TimeslicePeriod::get()) as u64));
+	let jumped = run(set_timeslice);
+	assert_eq!(stepped, jumped);
+	assert_eq!(stepped.0, 26);
+}
+
+#[test]
+fn overlapping_reservation_is_rejected() {
+	TestExt::new().execute_with(|| {
+		let item = |task, from, to| ScheduleItem {
+			assignment: Task(task),
+			mask: CoreMask::from_chunk(from, to),
+		};
+		let overlapping = Schedule::truncate_from(vec![item(1001, 0, 40), item(1002, 30, 80)]);
+		assert_noop!(
+			Broker::do_reserve(overlapping.clone()),
+			Error::<Test>::OverlappingReservation
+		);
+		assert_noop!(
+			Broker::do_set_reservation(0, overlapping),
+			Error::<Test>::OverlappingReservation
+		);
+		let disjoint = Schedule::truncate_from(vec![item(1001, 0, 40), item(1002, 40, 80)]);
+		assert_ok!(Broker::do_reserve(disjoint));
+		assert_eq!(Reservations::<Test>::get().len(), 1);
+	});
+}
+
+#[test]
+fn reservations_are_reported_to_hook() {
+	TestExt::new().execute_with(|| {
+		advance_to(1);
+		let item = ScheduleItem { assignment: Task(1001), mask: CoreMask::complete() };
+		let workload = Schedule::truncate_from(vec![item]);
+		assert_ok!(Broker::do_reserve(workload.clone()));
+		System::assert_last_event(
+			Event::ReservationMade { index: 0, workload: workload.clone() }.into(),
+		);
+		assert_eq!(ReserveCalls::get(), vec![(0, workload.clone())]);
+
+		// The hook may refuse a reservation.
+		ReservationBudget::set(Some(1));
+		assert_noop!(
+			Broker::do_reserve(workload.clone()),
+			sp_runtime::DispatchError::Other("Reservation budget exhausted")
+		);
+		assert_eq!(Reservations::<Test>::get().len(), 1);
+		assert_eq!(ReserveCalls::get(), vec![(0, workload.clone())]);
+
+		// Reservations appended through `set_reservation` are reported and refusable too.
+		assert_noop!(
+			Broker::do_set_reservation(1, workload.clone()),
+			sp_runtime::DispatchError::Other("Reservation budget exhausted")
+		);
+		ReservationBudget::set(None);
+		assert_ok!(Broker::do_set_reservation(1, workload.clone()));
+		assert_eq!(ReserveCalls::get(), vec![(0, workload.clone()), (1, workload)]);
+	});
+}
+
+#[test]
+fn incomplete_reservation_is_rejected() {
+	TestExt::new().execute_with(|| {
+		let item = |task, from, to| ScheduleItem {
+			assignment: Task(task),
+			mask: CoreMask::from_chunk(from, to),
+		};
+		// Parts 40..60 would be left without an assignment.
+		let gap = Schedule::truncate_from(vec![item(1001, 0, 40), item(1002, 60, 80)]);
+		assert_noop!(Broker::do_reserve(gap.clone()), Error::<Test>::IncompleteSchedule);
+		assert_noop!(Broker::do_set_reservation(0, gap), Error::<Test>::IncompleteSchedule);
+		assert_noop!(
+			Broker::do_reserve(Schedule::truncate_from(vec![])),
+			Error::<Test>::IncompleteSchedule
+		);
+		let idle = ScheduleItem { assignment: Idle, mask: CoreMask::from_chunk(40, 60) };
+		let filled = Schedule::truncate_from(vec![item(1001, 0, 40), idle, item(1002, 60, 80)]);
+		assert_ok!(Broker::do_reserve(filled));
+		assert_eq!(Reservations::<Test>::get().len(), 1);
+	});
+}
+
+#[test]
+fn unclaimed_revenue_is_swept() {
+	TestExt::new().endow(1, 1000).endow(2, 30).execute_with(|| {
+		RevenueClaimPeriod::set(5);
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		// Active in pool timeslices 4, 5, 6 = rcblocks 8, 10, 12.
+		assert_ok!(Broker::do_pool(region, None, 3, Final));
+		assert_ok!(Broker::do_purchase_credit(2, 30, 2));
+		for block in [8, 10, 12] {
+			advance_to(block);
+			assert_ok!(TestCoretimeProvider::spend_instantaneous(2, 10));
+		}
+		let unclaimed =
+			|| InstaPoolHistory::<Test>::iter_keys().filter(|t| (4..=6).contains(t)).count();
+		advance_to(18);
+		// All revenue has been reported, but none has been claimed.
+		assert_eq!(pot(), 30);
+		assert_eq!(revenue(), 100);
+		assert_eq!(unclaimed(), 3);
+		advance_to(22);
+		// Timeslices 4 and 5 are now more than 5 timeslices in the past.
+		assert_eq!(unclaimed(), 1);
+		assert_eq!(pot(), 10);
+		advance_to(24);
+		assert_eq!(unclaimed(), 0);
+		assert_eq!(pot(), 0);
+		assert_eq!(revenue(), 130);
+		let region_id = RegionId { begin: 6, ..region };
+		System::assert_has_event(Event::RevenueSwept { region_id, amount: 10 }.into());
+		// The contribution has been claimed in full on the payee's behalf.
+		assert_noop!(
+			Broker::do_claim_revenue(region, 100, None),
+			Error::<Test>::UnknownContribution
+		);
+		assert_eq!(balance(3), 0);
+	});
+}
+
+#[test]
+fn unreported_revenue_is_not_swept() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		RevenueClaimPeriod::set(5);
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, None, 3, Final));
+		// The Relay-chain never reports the revenue of the pool.
+		for block in 3..=24 {
+			advance_to(block);
+			NotifyRevenueInfo::set(vec![]);
+		}
+		assert_eq!(
+			InstaPoolContribution::<Test>::get(region),
+			Some(ContributionRecord { length: 3, payee: 3 })
+		);
+		assert!((4..7).all(|t| InstaPoolHistory::<Test>::get(t).unwrap().maybe_payout.is_none()));
+	});
+}
+
+#[test]
+fn interlace_range_works() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 2, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let other_core = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let (region1, region2) = Broker::do_partition(region, None, 2).unwrap();
+		let pivot = CoreMask::from_chunk(0, 40);
+		let regions = |ids: Vec<RegionId>| CoreRegions::truncate_from(ids);
+
+		assert_noop!(
+			Broker::do_interlace_range(regions(vec![region1, other_core]), None, pivot),
+			Error::<Test>::DifferentCores
+		);
+		assert_noop!(
+			Broker::do_interlace_range(regions(vec![region1, region2]), Some(2), pivot),
+			Error::<Test>::NotOwner
+		);
+
+		let new_regions =
+			Broker::do_interlace_range(regions(vec![region1, region2]), Some(1), pivot).unwrap();
+		let complement = CoreMask::from_chunk(40, 80);
+		assert_eq!(
+			new_regions,
+			vec![
+				(RegionId { mask: pivot, ..region1 }, RegionId { mask: complement, ..region1 }),
+				(RegionId { mask: pivot, ..region2 }, RegionId { mask: complement, ..region2 }),
+			]
+		);
+		assert!(Regions::<Test>::get(region1).is_none());
+		assert!(Regions::<Test>::get(region2).is_none());
+		assert_eq!(Regions::<Test>::get(new_regions[1].1).unwrap().end, 7);
+
+		// Every Region must contain the whole pivot.
+		let halves = regions(vec![new_regions[0].0, new_regions[1].1]);
+		assert_noop!(
+			Broker::do_interlace_range(halves, None, CoreMask::from_chunk(0, 20)),
+			Error::<Test>::ExteriorPivot
+		);
+	});
+}
+
+#[test]
+fn failures_report_specific_errors() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::Uninitialized);
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::TooEarly);
+		advance_to(2);
+		assert_noop!(Broker::do_purchase(1, 99), Error::<Test>::Overpriced);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::SoldOut);
+
+		assert_noop!(Broker::do_transfer(region, Some(2), 2), Error::<Test>::NotOwner);
+		let unknown = RegionId { begin: 5, ..region };
+		assert_noop!(Broker::do_assign(unknown, None, 1001, Final), Error::<Test>::UnknownRegion);
+		assert_noop!(Broker::do_set_lease(1000, 1), Error::<Test>::AlreadyExpired);
+
+		// The pooled Region ends with the next sale's Regions, not at their beginning.
+		assert_ok!(Broker::do_pool(region, Some(1), 1, Final));
+		assert_noop!(Broker::do_renew_pooled(1, region), Error::<Test>::WrongTime);
+	});
+}
+
+#[test]
+fn notify_revenue_pays_contributors() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, None, 2, Final));
+		// Fund the pot, as credit purchases would.
+		assert_ok!(Broker::do_purchase_credit(1, 20, 1));
+		advance_to(10);
+		let revenue = RevenueInfo { until: 10, amount: 20 };
+		assert_noop!(
+			Broker::notify_revenue(RuntimeOrigin::signed(2), revenue),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Broker::notify_revenue(RuntimeOrigin::root(), revenue));
+		assert_noop!(Broker::do_notify_revenue(revenue), Error::<Test>::RevenuePending);
+		advance_to(11);
+		// The reported revenue is that of timeslice 4, to which the whole core was contributed.
+		assert_eq!(InstaPoolHistory::<Test>::get(4).unwrap().maybe_payout, Some(20));
+		assert_ok!(Broker::do_claim_revenue(region, 1, None));
+		assert_eq!(balance(2), 20);
+	});
+}
+
+#[test]
+fn pool_complement_of_assigned_part_works() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let (task_part, pool_part) =
+			Broker::do_interlace(region, None, CoreMask::from_chunk(0, 40)).unwrap();
+		assert_ok!(Broker::do_assign(task_part, None, 1001, Final));
+		assert_ok!(Broker::do_pool(pool_part, None, 2, Final));
+		advance_to(6);
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![(
+				6,
+				AssignCore {
+					core: 0,
+					begin: 8,
+					assignment: vec![(Pool, 28800), (Task(1001), 28800)],
+					end_hint: None
+				}
+			)]
+		);
+		assert_eq!(InstaPoolIo::<Test>::get(4).private, 40);
+	});
+}
+
+#[test]
+fn unpooled_region_can_be_assigned() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let paid = Regions::<Test>::get(&region).unwrap().paid.unwrap();
+		assert_ok!(Broker::do_pool(region, None, 2, Provisional));
+		advance_to(7);
+		// The owner of the Region controls the contribution, not the payee.
+		assert_noop!(Broker::do_unpool(region, Some(2), 10), Error::<Test>::NotOwner);
+		// Timeslice 4 has been committed to the Pool, so the Region is restored from timeslice 5.
+		let restored = Broker::do_unpool(region, Some(1), 10).unwrap();
+		assert_eq!(restored, RegionId { begin: 5, ..region });
+		assert_eq!(
+			Regions::<Test>::get(&restored),
+			Some(RegionRecord { end: 7, owner: 1, paid: Some(paid * 2 / 3) })
+		);
+		assert_eq!(Regions::<Test>::get(&region), None);
+		assert_eq!(AllowedRenewals::<Test>::iter().count(), 0);
+		System::assert_last_event(Event::Unpooled { region_id: restored, duration: 2 }.into());
+		// The revenue of timeslice 4 is not yet known and remains claimable.
+		assert_eq!(InstaPoolContribution::<Test>::get(&region).map(|c| c.length), Some(1));
+		assert_eq!(InstaPoolIo::<Test>::get(5).private, -80);
+		assert_eq!(InstaPoolIo::<Test>::get(7).private, 0);
+
+		assert_ok!(Broker::do_assign(restored, Some(1), 1001, Final));
+		advance_to(9);
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![
+				(
+					6,
+					AssignCore {
+						core: 0,
+						begin: 8,
+						assignment: vec![(Pool, 57600)],
+						end_hint: None
+					}
+				),
+				(
+					8,
+					AssignCore {
+						core: 0,
+						begin: 10,
+						assignment: vec![(Task(1001), 57600)],
+						end_hint: None
+					}
+				),
+			]
+		);
+		assert_noop!(Broker::do_unpool(region, Some(1), 10), Error::<Test>::AlreadyExpired);
+	});
+}
+
+#[test]
+fn final_contribution_cannot_be_unpooled() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_pool(region, Some(1), 2, Final));
+		let renewal = AllowedRenewalId { core: region.core, when: 7 };
+		assert!(AllowedRenewals::<Test>::get(renewal).is_some());
+		advance_to(3);
+		assert_noop!(Broker::do_unpool(region, Some(1), 10), Error::<Test>::FinalContribution);
+		assert_noop!(Broker::do_unpool(region, Some(2), 10), Error::<Test>::FinalContribution);
+		// The renewal made possible by the contribution remains valid.
+		assert!(AllowedRenewals::<Test>::get(renewal).is_some());
+		advance_to(6);
+		assert_ok!(Broker::do_renew_pooled(1, region));
+	});
+}
+
+#[test]
+fn cannot_pool_part_already_assigned() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_assign(region, None, 1001, Provisional));
+		advance_to(7);
+		// Timeslice 4 has been committed, so pooling now starts at timeslice 5, where some
+		// mishap has left part of the core assigned.
+		let item = ScheduleItem { assignment: Task(1002), mask: CoreMask::from_chunk(0, 20) };
+		Workplan::<Test>::insert((5, 0), Schedule::truncate_from(vec![item]));
+		assert_err!(Broker::do_pool(region, Some(1), 2, Final), Error::<Test>::PartAlreadyAssigned);
+	});
+}
+
+#[test]
+fn provisionally_assigned_region_can_be_pooled() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_assign(region, None, 1001, Provisional));
+		assert_ok!(Broker::do_pool(region, Some(1), 2, Final));
+		let just_pool =
+			Schedule::truncate_from(vec![ScheduleItem { assignment: Pool, mask: region.mask }]);
+		assert_eq!(Workplan::<Test>::get((4, 0)), Some(just_pool));
+		assert!(InstaPoolContribution::<Test>::get(region).is_some());
+	});
+}
+
+#[test]
+fn region_end_accounts_for_partitions() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_eq!(Broker::region_end(region), Some(7));
+		let (region1, region2) = Broker::do_partition(region, None, 1).unwrap();
+		assert_eq!(Broker::region_end(region1), Some(5));
+		assert_eq!(Broker::region_end(region2), Some(7));
+		assert_ok!(Broker::do_pool(region2, None, 1, Final));
+		assert_eq!(Broker::region_end(region2), Some(7));
+		assert_ok!(Broker::do_assign(region1, None, 1001, Final));
+		assert_eq!(Broker::region_end(region1), None);
+	});
+}
+
+#[test]
+fn try_state_detects_corrupted_storage() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let (region1, region2) = Broker::do_partition(region, None, 1).unwrap();
+		assert_ok!(Broker::do_pool(region2, None, 1, Final));
+		assert_ok!(Broker::do_try_state());
+
+		// A Region overlapping the pooled one on part of the same core.
+		let overlapping = RegionId { begin: 6, core: 0, mask: CoreMask::from_chunk(0, 10) };
+		let record = RegionRecord { end: 7, owner: 2, paid: None };
+		Regions::<Test>::insert(&overlapping, &record);
+		assert_err!(Broker::do_try_state(), "Regions share a part of a core");
+		Regions::<Test>::remove(&overlapping);
+
+		// A Region which is also pooled, but not for all of its span.
+		let record = RegionRecord { end: 6, owner: 1, paid: None };
+		Regions::<Test>::insert(&region2, &record);
+		assert_err!(Broker::do_try_state(), "A Region is both held and pooled for different spans");
+		Regions::<Test>::remove(&region2);
+
+		// A payout for which the pot holds no funds.
+		let history = InstaPoolHistoryRecord {
+			private_contributions: 80,
+			system_contributions: 0,
+			maybe_payout: Some(pot() + 1),
+		};
+		InstaPoolHistory::<Test>::insert(5, &history);
+		assert_err!(Broker::do_try_state(), "The pot does not cover payouts owed");
+		InstaPoolHistory::<Test>::remove(5);
+
+		assert_ok!(Broker::do_try_state());
+		assert!(Regions::<Test>::contains_key(&region1));
+	});
+}
+
+#[test]
+fn try_state_accepts_provisional_contribution() {
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let (region1, region2) = Broker::do_partition(region, None, 1).unwrap();
+		assert_ok!(Broker::do_pool(region2, None, 1, Provisional));
+		assert!(Regions::<Test>::contains_key(&region2));
+		assert!(InstaPoolContribution::<Test>::contains_key(&region2));
+		assert_ok!(Broker::do_try_state());
+
+		// Another Region on the same part of the core is still caught.
+		let record = RegionRecord { end: 7, owner: 2, paid: None };
+		Regions::<Test>::insert(&RegionId { begin: 6, ..region2 }, &record);
+		assert_err!(Broker::do_try_state(), "Regions share a part of a core");
+		Regions::<Test>::remove(&RegionId { begin: 6, ..region2 });
+
+		assert_ok!(Broker::do_unpool(region2, Some(1), 0));
+		assert_ok!(Broker::do_try_state());
+		assert!(Regions::<Test>::contains_key(&region1));
+	});
+}
+
+#[test]
+fn assignment_grace_only_hints_changing_workload() {
+	TestExt::new().endow(1, 1000).assignment_grace(1).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_ok!(Broker::do_assign(region, None, 1001, Final));
+		// The core goes to a reservation from the next sale on.
+		let item = ScheduleItem { assignment: Task(2000), mask: CoreMask::complete() };
+		assert_ok!(Broker::do_reserve(Schedule::truncate_from(vec![item])));
+		advance_to(12);
+		assert_eq!(
+			CoretimeTrace::get(),
+			vec![
+				(
+					6,
+					AssignCore {
+						core: 0,
+						begin: 8,
+						assignment: vec![(Task(1001), 57600)],
+						end_hint: Some(15)
+					}
+				),
+				// The reservation goes on unchanged into the following sale, so it has no hint.
+				(
+					12,
+					AssignCore {
+						core: 0,
+						begin: 14,
+						assignment: vec![(Task(2000), 57600)],
+						end_hint: None
+					}
+				),
+			]
+		);
+	});
+}
+
+#[test]
+fn migration_to_v1_keeps_regions() {
+	use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+	use migration::v1::MigrateToV1;
+
+	TestExt::new().endow(1, 1000).execute_with(|| {
+		assert_ok!(Broker::do_start_sales(100, 1, None));
+		advance_to(2);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		let record = Regions::<Test>::get(&region);
+		StorageVersion::new(0).put::<Broker>();
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<Broker>(), 1);
+		assert_eq!(Regions::<Test>::get(&region), record);
+		assert_eq!(record.as_ref().unwrap().paid, Some(100));
+
+		// Running it again changes nothing.
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<Broker>(), 1);
+		assert_eq!(Regions::<Test>::get(&region), record);
+		assert_eq!(Regions::<Test>::iter().count(), 1);
+	});
+}
+
+#[test]
+fn sale_status_follows_the_cycle() {
+	TestExt::new()
+		.interlude_length(2)
+		.leadin_length(3)
+		.endow(1, 1000)
+		.execute_with(|| {
+			assert_eq!(Broker::sale_status(), SaleStatus::NotStarted);
+			assert_ok!(Broker::do_start_sales(100, 2, None));
+			assert_eq!(Broker::sale_status(), SaleStatus::Interlude { opens_in: 3 });
+			advance_to(1);
+			assert_eq!(Broker::sale_status(), SaleStatus::Interlude { opens_in: 2 });
+			// Purchasing opens only after the block in which the sale starts.
+			advance_to(2);
+			assert_eq!(Broker::sale_status(), SaleStatus::Interlude { opens_in: 1 });
+			assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::TooEarly);
+			advance_to(3);
+			assert_eq!(Broker::sale_status(), SaleStatus::Leadin { ends_in: 2 });
+			assert_ok!(Broker::do_purchase(1, u64::max_value()));
+			advance_to(4);
+			assert_eq!(Broker::sale_status(), SaleStatus::Leadin { ends_in: 1 });
+			advance_to(5);
+			assert_eq!(Broker::sale_status(), SaleStatus::Purchasing);
+			assert_ok!(Broker::do_purchase(1, u64::max_value()));
+			assert_eq!(Broker::sale_status(), SaleStatus::SoldOut);
+			// The next sale begins with its interlude.
+			advance_to(6);
+			assert_eq!(Broker::sale_status(), SaleStatus::Interlude { opens_in: 3 });
+		});
+}
+
+#[test]
+fn first_cycle_can_be_aligned() {
+	TestExt::new().interlude_length(3).endow(1, 1000).execute_with(|| {
+		// Timeslice 1 is already ready to be committed.
+		assert_noop!(Broker::do_start_sales(100, 1, Some(1)), Error::<Test>::FirstCycleTooEarly);
+		assert_ok!(Broker::do_start_sales(100, 1, Some(6)));
+		let sale = SaleInfo::<Test>::get().unwrap();
+		assert_eq!((sale.region_begin, sale.region_end), (6, 9));
+		// No purchases during the interlude.
+		advance_to(3);
+		assert_noop!(Broker::do_purchase(1, u64::max_value()), Error::<Test>::TooEarly);
+		advance_to(4);
+		let region = Broker::do_purchase(1, u64::max_value()).unwrap();
+		assert_eq!(region.begin, 6);
+		// The sale rotates once its Regions are committed.
+		advance_to(9);
+		assert_eq!(SaleInfo::<Test>::get().unwrap().region_begin, 6);
+		advance_to(10);
+		assert_eq!(SaleInfo::<Test>::get().unwrap().region_begin, 9);
+	});
+}
@@ -16,15 +16,26 @@
 // limitations under the License.
 
 use super::*;
-use frame_support::{pallet_prelude::*, weights::WeightMeter};
+use frame_support::{
+	pallet_prelude::*,
+	storage::{with_transaction, TransactionOutcome},
+	traits::DefensiveSaturating,
+	weights::WeightMeter,
+};
 use sp_arithmetic::{
-	traits::{One, SaturatedConversion, Saturating, Zero},
-	FixedPointNumber,
+	traits::{Bounded, One, SaturatedConversion, Saturating, Zero},
+	FixedPointNumber, PerThing,
 };
-use sp_runtime::traits::ConvertBack;
+use sp_runtime::traits::{Convert, ConvertBack};
 use sp_std::{vec, vec::Vec};
 use CompletionStatus::Complete;
 
+/// The maximum number of expired credit grants swept in a single tick.
+const MAX_CREDIT_EXPIRIES_PER_TICK: u32 = 64;
+
+/// The maximum number of pool contributions visited for unclaimed revenue in a single tick.
+const MAX_REVENUE_SWEEPS_PER_TICK: u32 = 64;
+
 impl<T: Config> Pallet<T> {
 	/// Attempt to tick things along.
 	///
@@ -73,6 +84,14 @@ impl<T: Config> Pallet<T> {
 		}
 
 		let current_timeslice = Self::current_timeslice();
+		let swept = Self::process_credit_expiry(current_timeslice);
+		meter.consume(T::DbWeight::get().reads_writes(swept as u64 * 2 + 2, swept as u64 * 3 + 1));
+		let (visited, swept) = Self::process_revenue_sweep(current_timeslice);
+		meter.consume(T::DbWeight::get().reads_writes(visited as u64 * 2 + 1, 1));
+		if swept > 0 {
+			meter.consume(T::WeightInfo::claim_revenue(swept));
+		}
+
 		if status.last_timeslice < current_timeslice {
 			status.last_timeslice.saturating_inc();
 			let rc_block = T::TimeslicePeriod::get() * status.last_timeslice.into();
@@ -95,12 +114,14 @@ impl<T: Config> Pallet<T> {
 	}
 
 	pub(crate) fn process_revenue() -> bool {
-		let Some((until, amount)) = T::Coretime::check_notify_revenue_info() else {
+		let notified = RevenueInbox::<T>::take().map(|r| (r.until, r.amount));
+		let Some((until, amount)) = notified.or_else(T::Coretime::check_notify_revenue_info) else {
 			return false;
 		};
 		let when: Timeslice =
 			(until / T::TimeslicePeriod::get()).saturating_sub(One::one()).saturated_into();
-		let mut revenue = T::ConvertBalance::convert_back(amount);
+		let mut revenue = T::ConvertBalance::convert_back(amount)
+			.defensive_saturating_add(InstaPoolOrderRevenue::<T>::take(when));
 		if revenue.is_zero() {
 			Self::deposit_event(Event::<T>::HistoryDropped { when, revenue });
 			InstaPoolHistory::<T>::remove(when);
@@ -113,8 +134,7 @@ impl<T: Config> Pallet<T> {
 		}
 		// Payout system InstaPool Cores.
 		let total_contrib = r.system_contributions.saturating_add(r.private_contributions);
-		let system_payout =
-			revenue.saturating_mul(r.system_contributions.into()) / total_contrib.into();
+		let system_payout = Self::pro_rata(revenue, r.system_contributions, total_contrib);
 		let _ = Self::charge(&Self::account_id(), system_payout);
 		revenue.saturating_reduce(system_payout);
 
@@ -133,6 +153,110 @@ impl<T: Config> Pallet<T> {
 		true
 	}
 
+	/// Pass to `OnRevenue` the revenue of Instantaneous Coretime Pool contributions which has
+	/// gone unclaimed since more than `RevenueClaimPeriod` timeslices before `now`. Each
+	/// contribution is keyed by the first timeslice it has yet to claim, which thus serves as its
+	/// claim cursor; the sweep claims on its behalf up to the deadline, so the history records
+	/// are dropped as `do_claim_revenue` would drop them. Revenue which is not yet known is left
+	/// to be claimed. At most `MAX_REVENUE_SWEEPS_PER_TICK` contributions are visited, continuing
+	/// from where the previous sweep left off.
+	///
+	/// Returns the number of contributions visited and of timeslices swept.
+	pub(crate) fn process_revenue_sweep(now: Timeslice) -> (u32, u32) {
+		let Some(stale_before) = now.checked_sub(T::RevenueClaimPeriod::get()) else {
+			return (0, 0)
+		};
+		let contributions: Vec<(RegionId, ContributionRecordOf<T>)> =
+			match RevenueSweepCursor::<T>::get() {
+				Some(last) => InstaPoolContribution::<T>::iter_from_key(last),
+				None => InstaPoolContribution::<T>::iter(),
+			}
+			.take(MAX_REVENUE_SWEEPS_PER_TICK as usize)
+			.collect();
+		// Start over once every contribution has been visited.
+		let cursor = contributions
+			.last()
+			.filter(|_| contributions.len() == MAX_REVENUE_SWEEPS_PER_TICK as usize)
+			.map(|(region_id, _)| *region_id);
+		RevenueSweepCursor::<T>::set(cursor);
+
+		let mut swept = 0;
+		for (region_id, contribution) in
+			contributions.iter().filter(|(r, _)| r.begin < stale_before)
+		{
+			let end = region_id.begin.saturating_add(contribution.length).min(stale_before);
+			// Revenue is reported in order, so it is known for the whole span if known for its
+			// last timeslice.
+			let last = end.saturating_sub(1);
+			if InstaPoolHistory::<T>::get(last).map_or(false, |h| h.maybe_payout.is_none()) {
+				continue
+			}
+			let timeslices = end.saturating_sub(region_id.begin);
+			// Hooks are not transactional, so each claim is, lest a failing one be left half done.
+			let claimed = with_transaction(|| -> TransactionOutcome<Result<_, DispatchError>> {
+				match Self::take_revenue(*region_id, timeslices) {
+					Ok((_, amount, _)) => {
+						let _ = Self::charge(&Self::account_id(), amount);
+						TransactionOutcome::Commit(Ok(amount))
+					},
+					Err(e) => TransactionOutcome::Rollback(Err(e)),
+				}
+			});
+			let Ok(amount) = claimed else { continue };
+			swept.saturating_accrue(timeslices);
+			Self::deposit_event(Event::<T>::RevenueSwept { region_id: *region_id, amount });
+		}
+		(contributions.len() as u32, swept)
+	}
+
+	/// Sweep credit which expired at or before `now`, handling at most
+	/// `MAX_CREDIT_EXPIRIES_PER_TICK` grants. The unspent part of each grant is revoked on the
+	/// Relay-chain and the corresponding funds are passed to `OnRevenue`.
+	///
+	/// Credit is spent oldest grant first, so an expiring grant is unspent only as far as the
+	/// account holds more credit than its later grants, as recorded in `CreditOutstanding`.
+	///
+	/// Returns the number of grants and timeslices handled.
+	pub(crate) fn process_credit_expiry(now: Timeslice) -> u32 {
+		let start = CreditExpiryCursor::<T>::get();
+		let mut cursor = start.unwrap_or(now);
+		let mut handled = 0;
+		while cursor <= now && handled < MAX_CREDIT_EXPIRIES_PER_TICK {
+			let mut expired = CreditExpiries::<T>::drain_prefix(cursor);
+			while handled < MAX_CREDIT_EXPIRIES_PER_TICK {
+				let Some((who, granted)) = expired.next() else {
+					break;
+				};
+				handled.saturating_inc();
+				let later = CreditOutstanding::<T>::mutate_exists(&who, |o| {
+					let later = o.unwrap_or_default().saturating_sub(granted);
+					*o = Some(later).filter(|l| !l.is_zero());
+					later
+				});
+				let held =
+					T::Coretime::revoke_credit(who.clone(), RelayBalanceOf::<T>::max_value());
+				let unspent = held
+					.saturating_sub(T::ConvertBalance::convert(later))
+					.min(T::ConvertBalance::convert(granted));
+				if held > unspent {
+					T::Coretime::credit_account(who.clone(), held.saturating_sub(unspent));
+				}
+				let amount = T::ConvertBalance::convert_back(unspent);
+				let _ = Self::charge(&Self::account_id(), amount);
+				Self::deposit_event(Event::<T>::CreditExpired { who, amount });
+			}
+			if CreditExpiries::<T>::iter_prefix(cursor).next().is_some() {
+				break
+			}
+			cursor.saturating_inc();
+			handled.saturating_inc();
+		}
+		if start != Some(cursor) {
+			CreditExpiryCursor::<T>::put(cursor);
+		}
+		handled
+	}
+
 	/// Begin selling for the next sale period.
 	///
 	/// Triggered by Relay-chain block number/timeslice.
@@ -147,16 +271,47 @@ impl<T: Config> Pallet<T> {
 			ScheduleItem { assignment: CoreAssignment::Pool, mask: CoreMask::complete() };
 		let just_pool = Schedule::truncate_from(vec![pool_item]);
 
-		// Clean up the old sale - we need to use up any unused cores by putting them into the
-		// InstaPool.
+		// Clean up the old sale - unless configured otherwise, we need to use up any unused cores
+		// by putting them into the InstaPool.
 		let mut old_pooled: SignedCoreMaskBitCount = 0;
-		for i in old_sale.cores_sold..old_sale.cores_offered {
-			old_pooled.saturating_accrue(80);
-			Workplan::<T>::insert((old_sale.region_begin, old_sale.first_core + i), &just_pool);
+		let partially_sold = PartiallySoldCore::<T>::take();
+		let chosen = ChosenCores::<T>::take();
+		let cancelled = CancelledSale::<T>::take().is_some();
+		if T::AutoPoolUnsold::get() && !cancelled {
+			let duration = old_sale.region_end.saturating_sub(old_sale.region_begin);
+			for core in Self::unsold_cores(&old_sale, &chosen) {
+				old_pooled.saturating_accrue(80);
+				Workplan::<T>::insert((old_sale.region_begin, core), &just_pool);
+				let region_id =
+					RegionId { begin: old_sale.region_begin, core, mask: CoreMask::complete() };
+				Self::deposit_event(Event::UnsoldAutoPooled { region_id, duration });
+			}
+			if let Some((core, sold)) = partially_sold {
+				let unsold = !sold;
+				old_pooled.saturating_accrue(unsold.count_ones() as SignedCoreMaskBitCount);
+				let pool_item = ScheduleItem { assignment: CoreAssignment::Pool, mask: unsold };
+				Workplan::<T>::mutate((old_sale.region_begin, core), |p| {
+					let _ = p.get_or_insert_with(Default::default).try_push(pool_item);
+				});
+				let region_id = RegionId { begin: old_sale.region_begin, core, mask: unsold };
+				Self::deposit_event(Event::UnsoldAutoPooled { region_id, duration });
+			}
 		}
 		InstaPoolIo::<T>::mutate(old_sale.region_begin, |r| r.system.saturating_accrue(old_pooled));
 		InstaPoolIo::<T>::mutate(old_sale.region_end, |r| r.system.saturating_reduce(old_pooled));
 
+		// Fold the price at which the old sale concluded into the moving average.
+		if old_sale.cores_offered > 0 {
+			let concluded = old_sale.sellout_price.unwrap_or(old_sale.price);
+			let factor = T::PriceEmaFactor::get();
+			PriceEma::<T>::mutate(|ema| {
+				*ema = Some(match *ema {
+					Some(ema) => (factor * concluded).saturating_add(factor.left_from_one() * ema),
+					None => concluded,
+				})
+			});
+		}
+
 		// Calculate the start price for the upcoming sale.
 		let price = {
 			let offered = old_sale.cores_offered;
@@ -250,6 +405,7 @@ impl<T: Config> Pallet<T> {
 			cores_sold: 0,
 		};
 		SaleInfo::<T>::put(&new_sale);
+		SaleIndex::<T>::mutate(|i| i.saturating_inc());
 		Self::deposit_event(Event::SaleInitialized {
 			sale_start,
 			leadin_length,
@@ -283,6 +439,30 @@ impl<T: Config> Pallet<T> {
 		});
 	}
 
+	/// The `end_hint` for the assignment `workload` of `core` beginning at `timeslice`:
+	/// `AssignmentGrace` blocks past the next sale boundary if the workload of the core changes
+	/// there, or `None` if it stays the same, there is no grace or no sale.
+	pub(crate) fn assignment_end_hint(
+		timeslice: Timeslice,
+		core: CoreIndex,
+		workload: &Schedule,
+	) -> Option<RelayBlockNumberOf<T>> {
+		let grace = T::AssignmentGrace::get();
+		if grace.is_zero() {
+			return None
+		}
+		let sale = SaleInfo::<T>::get()?;
+		let boundary =
+			if timeslice < sale.region_begin { sale.region_begin } else { sale.region_end };
+		let next = Workplan::<T>::get((boundary, core))?;
+		let next = Self::merge_schedule(next, workload.clone());
+		if Self::schedule_to_assignment(next) == Self::schedule_to_assignment(workload.clone()) {
+			return None
+		}
+		let rc_boundary = RelayBlockNumberOf::<T>::from(boundary) * T::TimeslicePeriod::get();
+		Some(rc_boundary.saturating_add(grace))
+	}
+
 	/// Schedule cores for the given `timeslice`.
 	pub(crate) fn process_core_schedule(
 		timeslice: Timeslice,
@@ -292,35 +472,12 @@ impl<T: Config> Pallet<T> {
 		let Some(workplan) = Workplan::<T>::take((timeslice, core)) else {
 			return;
 		};
-		let workload = Workload::<T>::get(core);
-		let parts_used = workplan.iter().map(|i| i.mask).fold(CoreMask::void(), |a, i| a | i);
-		let mut workplan = workplan.into_inner();
-		workplan.extend(workload.into_iter().filter(|i| (i.mask & parts_used).is_void()));
-		let workplan = Schedule::truncate_from(workplan);
+		let workplan = Self::merge_schedule(workplan, Workload::<T>::get(core));
 		Workload::<T>::insert(core, &workplan);
 
-		let mut total_used = 0;
-		let mut intermediate = workplan
-			.into_iter()
-			.map(|i| (i.assignment, i.mask.count_ones() as u16 * (57_600 / 80)))
-			.inspect(|i| total_used.saturating_accrue(i.1))
-			.collect::<Vec<_>>();
-		if total_used < 57_600 {
-			intermediate.push((CoreAssignment::Idle, 57_600 - total_used));
-		}
-		intermediate.sort();
-		let mut assignment: Vec<(CoreAssignment, PartsOf57600)> =
-			Vec::with_capacity(intermediate.len());
-		for i in intermediate.into_iter() {
-			if let Some(ref mut last) = assignment.last_mut() {
-				if last.0 == i.0 {
-					last.1 += i.1;
-					continue
-				}
-			}
-			assignment.push(i);
-		}
-		T::Coretime::assign_core(core, rc_begin, assignment.clone(), None);
+		let end_hint = Self::assignment_end_hint(timeslice, core, &workplan);
+		let assignment = Self::schedule_to_assignment(workplan);
+		T::Coretime::assign_core(core, rc_begin, assignment.clone(), end_hint);
 		Self::deposit_event(Event::<T>::CoreAssigned { core, when: rc_begin, assignment });
 	}
 }
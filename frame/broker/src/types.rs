@@ -19,7 +19,7 @@ use crate::{
 	Config, CoreAssignment, CoreIndex, CoreMask, CoretimeInterface, TaskId, CORE_MASK_BITS,
 };
 use codec::{Decode, Encode, MaxEncodedLen};
-use frame_support::traits::fungible::Inspect;
+use frame_support::traits::{fungible::Inspect, fungibles};
 use frame_system::{pallet_prelude::BlockNumberFor, Config as SConfig};
 use scale_info::TypeInfo;
 use sp_arithmetic::Perbill;
@@ -28,6 +28,10 @@ use sp_runtime::BoundedVec;
 
 pub type BalanceOf<T> = <<T as Config>::Currency as Inspect<<T as SConfig>::AccountId>>::Balance;
 pub type RelayBalanceOf<T> = <<T as Config>::Coretime as CoretimeInterface>::Balance;
+pub type AssetIdOf<T> =
+	<<T as Config>::Assets as fungibles::Inspect<<T as SConfig>::AccountId>>::AssetId;
+pub type AssetBalanceOf<T> =
+	<<T as Config>::Assets as fungibles::Inspect<<T as SConfig>::AccountId>>::Balance;
 pub type RelayBlockNumberOf<T> = <<T as Config>::Coretime as CoretimeInterface>::BlockNumber;
 pub type RelayAccountIdOf<T> = <<T as Config>::Coretime as CoretimeInterface>::AccountId;
 
@@ -84,11 +88,16 @@ pub struct RegionRecord<AccountId, Balance> {
 	pub end: Timeslice,
 	/// The owner of the Region.
 	pub owner: AccountId,
-	/// The amount paid to Polkadot for this Region, or `None` if renewal is not allowed.
+	/// The amount paid to Polkadot for this Region, or `None` if it was not paid for. This is
+	/// pro-rated by timeslice when the Region is partitioned and kept in full by both Regions
+	/// when it is interlaced.
 	pub paid: Option<Balance>,
 }
 pub type RegionRecordOf<T> = RegionRecord<<T as SConfig>::AccountId, BalanceOf<T>>;
 
+/// A short label attached to a Region by its owner.
+pub type RegionMetadataOf<T> = BoundedVec<u8, <T as Config>::MaxMetadataLen>;
+
 /// An distinct item which can be scheduled on a Polkadot Core.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub struct ScheduleItem {
@@ -99,6 +108,15 @@ pub struct ScheduleItem {
 }
 pub type Schedule = BoundedVec<ScheduleItem, ConstU32<{ CORE_MASK_BITS as u32 }>>;
 
+/// A set of tasks, each with the relative weight of the parts of a Region it is to be assigned.
+pub type WeightedAssignments = BoundedVec<(TaskId, u16), ConstU32<{ CORE_MASK_BITS as u32 }>>;
+
+/// A set of Regions of the same core to be handled in a single call.
+pub type CoreRegions = BoundedVec<RegionId, ConstU32<16>>;
+
+/// The cores of a sale which were purchased by index rather than in order, kept sorted.
+pub type ChosenCoresRecord = BoundedVec<CoreIndex, ConstU32<64>>;
+
 /// The record body of a Region which was contributed to the Instantaneous Coretime Pool. This helps
 /// with making pro rata payments to contributors.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
@@ -126,6 +144,16 @@ pub struct InstaPoolHistoryRecord<Balance> {
 }
 pub type InstaPoolHistoryRecordOf<T> = InstaPoolHistoryRecord<BalanceOf<T>>;
 
+/// The revenue of the Instantaneous Coretime Pool, as reported by the Relay-chain.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct RevenueInfo<RelayBlockNumber, RelayBalance> {
+	/// The Relay-chain block number up to which the revenue was made.
+	pub until: RelayBlockNumber,
+	/// The amount of revenue made.
+	pub amount: RelayBalance,
+}
+pub type RevenueInfoOf<T> = RevenueInfo<RelayBlockNumberOf<T>, RelayBalanceOf<T>>;
+
 /// How much of a core has been assigned or, if completely assigned, the workload itself.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub enum CompletionStatus {
@@ -232,6 +260,28 @@ pub struct SaleInfoRecord<Balance, BlockNumber> {
 }
 pub type SaleInfoRecordOf<T> = SaleInfoRecord<BalanceOf<T>, BlockNumberFor<T>>;
 
+/// The phase which the ongoing sale is in.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum SaleStatus<BlockNumber> {
+	/// No sale has been initialized.
+	NotStarted,
+	/// The sale has been initialized but purchasing only opens in `opens_in` blocks.
+	Interlude {
+		/// The number of blocks until purchasing opens.
+		opens_in: BlockNumber,
+	},
+	/// Purchasing is open at a price which falls to the regular price in `ends_in` blocks.
+	Leadin {
+		/// The number of blocks until the price reaches the regular price.
+		ends_in: BlockNumber,
+	},
+	/// Purchasing is open at the regular price.
+	Purchasing,
+	/// Every core offered in the sale has been sold, or the sale has been cancelled.
+	SoldOut,
+}
+pub type SaleStatusOf<T> = SaleStatus<BlockNumberFor<T>>;
+
 /// Record for Polkadot Core reservations (generally tasked with the maintenance of System
 /// Chains).
 pub type ReservationsRecord<Max> = BoundedVec<Schedule, Max>;
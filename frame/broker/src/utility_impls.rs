@@ -19,17 +19,20 @@ use super::*;
 use frame_support::{
 	pallet_prelude::{DispatchResult, *},
 	traits::{
-		fungible::Balanced,
+		fungible::{self, Balanced, MutateHold},
+		fungibles,
 		tokens::{Fortitude::Polite, Precision::Exact, Preservation::Expendable},
-		OnUnbalanced,
+		DefensiveResult, OnUnbalanced,
 	},
 };
 use frame_system::pallet_prelude::BlockNumberFor;
 use sp_arithmetic::{
-	traits::{SaturatedConversion, Saturating},
+	traits::{One, SaturatedConversion, Saturating, Zero},
 	FixedPointNumber, FixedU64,
 };
-use sp_runtime::traits::AccountIdConversion;
+use sp_runtime::traits::{AccountIdConversion, Convert};
+use sp_std::{vec, vec::Vec};
+use CompletionStatus::{Complete, Partial};
 
 impl<T: Config> Pallet<T> {
 	pub fn current_timeslice() -> Timeslice {
@@ -63,7 +66,91 @@ impl<T: Config> Pallet<T> {
 	pub fn sale_price(sale: &SaleInfoRecordOf<T>, now: BlockNumberFor<T>) -> BalanceOf<T> {
 		let num = now.saturating_sub(sale.sale_start).min(sale.leadin_length).saturated_into();
 		let through = FixedU64::from_rational(num, sale.leadin_length.saturated_into());
-		T::PriceAdapter::leadin_factor_at(through).saturating_mul_int(sale.price)
+		T::PriceAdapter::leadin_factor_at(through)
+			.saturating_mul_int(sale.price)
+			.max(T::MinPrice::get())
+	}
+
+	/// The share of `amount` due to `parts` out of `total`, rounded down, or zero if `total` is
+	/// zero. The division is done first so that the result cannot saturate for large `amount`.
+	pub(crate) fn pro_rata(
+		amount: BalanceOf<T>,
+		parts: CoreMaskBitCount,
+		total: CoreMaskBitCount,
+	) -> BalanceOf<T> {
+		if total == 0 {
+			return Zero::zero()
+		}
+		let (parts, total): (BalanceOf<T>, BalanceOf<T>) = (parts.into(), total.into());
+		let whole = (amount / total).saturating_mul(parts);
+		whole.saturating_add((amount % total).saturating_mul(parts) / total)
+	}
+
+	/// The lowest-indexed core of `sale` which has been neither sold nor `chosen`. Cores not
+	/// chosen are always sold in ascending order, so they occupy the lowest indices not chosen.
+	pub(crate) fn next_free_core(sale: &SaleInfoRecordOf<T>, chosen: &[CoreIndex]) -> CoreIndex {
+		let in_order = sale.cores_sold.saturating_sub(chosen.len() as CoreIndex);
+		let mut core = sale.first_core.saturating_add(in_order);
+		for &c in chosen.iter() {
+			if c <= core {
+				core.saturating_inc();
+			}
+		}
+		core
+	}
+
+	/// The cores offered in `sale` which have not been sold.
+	pub(crate) fn unsold_cores(sale: &SaleInfoRecordOf<T>, chosen: &[CoreIndex]) -> Vec<CoreIndex> {
+		let end = sale.first_core.saturating_add(sale.cores_offered);
+		(Self::next_free_core(sale, chosen)..end)
+			.filter(|c| !chosen.contains(c))
+			.collect()
+	}
+
+	/// The timeslice at which the coverage of the Region `region_id` ceases, whether it is still
+	/// held or has been placed into the Instantaneous Coretime Pool.
+	pub fn region_end(region_id: RegionId) -> Option<Timeslice> {
+		match Regions::<T>::get(&region_id) {
+			Some(region) => Some(region.end),
+			None => InstaPoolContribution::<T>::get(&region_id)
+				.map(|c| region_id.begin.saturating_add(c.length)),
+		}
+	}
+
+	/// The earliest Relay-chain block at which the current sale will be rotated and the next one
+	/// initialized, i.e. `advance_notice` blocks before its Regions begin.
+	pub fn next_sale_rotation() -> Option<RelayBlockNumberOf<T>> {
+		let config = Configuration::<T>::get()?;
+		let sale = SaleInfo::<T>::get()?;
+		let region_begin = T::TimeslicePeriod::get().saturating_mul(sale.region_begin.into());
+		Some(region_begin.saturating_sub(config.advance_notice))
+	}
+
+	/// The phase which the ongoing sale is in as of the current block.
+	pub fn sale_status() -> SaleStatusOf<T> {
+		let Some(sale) = SaleInfo::<T>::get() else { return SaleStatus::NotStarted };
+		let now = frame_system::Pallet::<T>::block_number();
+		let leadin_end = sale.sale_start.saturating_add(sale.leadin_length);
+		if sale.cores_sold >= sale.cores_offered || CancelledSale::<T>::exists() {
+			SaleStatus::SoldOut
+		} else if now <= sale.sale_start {
+			// Purchasing only opens in the block after the sale starts.
+			SaleStatus::Interlude { opens_in: sale.sale_start - now + One::one() }
+		} else if now < leadin_end {
+			SaleStatus::Leadin { ends_in: leadin_end - now }
+		} else {
+			SaleStatus::Purchasing
+		}
+	}
+
+	/// The price of an on-demand order for a single Relay-chain block: the nominal price of a
+	/// Region in the ongoing sale pro rata.
+	pub fn spot_price() -> Result<BalanceOf<T>, Error<T>> {
+		let config = Configuration::<T>::get().ok_or(Error::<T>::Uninitialized)?;
+		let sale = SaleInfo::<T>::get().ok_or(Error::<T>::NoSales)?;
+		let timeslice_period: u32 = T::TimeslicePeriod::get().saturated_into();
+		let blocks = config.region_length.saturating_mul(timeslice_period).max(1);
+		Ok(sale.price / blocks.into())
 	}
 
 	pub(crate) fn charge(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
@@ -72,6 +159,46 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Take `amount` of the native currency from `who` as revenue or, if `maybe_asset` is given,
+	/// the equivalent amount of that asset as revenue for `OnAssetRevenue`.
+	pub(crate) fn charge_with(
+		who: &T::AccountId,
+		amount: BalanceOf<T>,
+		maybe_asset: Option<AssetIdOf<T>>,
+	) -> DispatchResult {
+		let Some(asset) = maybe_asset else { return Self::charge(who, amount) };
+		let asset_amount = T::AssetPrice::asset_price(asset.clone(), amount)
+			.ok_or(Error::<T>::UnsupportedAsset)?;
+		let credit = <T::Assets as fungibles::Balanced<_>>::withdraw(
+			asset,
+			who,
+			asset_amount,
+			Exact,
+			Expendable,
+			Polite,
+		)?;
+		T::OnAssetRevenue::on_unbalanced(credit);
+		Ok(())
+	}
+
+	/// Hold the `RegionDeposit` for the storage of the new Region `region_id` from `who`.
+	pub(crate) fn take_region_deposit(who: &T::AccountId, region_id: &RegionId) -> DispatchResult {
+		let amount = T::RegionDeposit::get();
+		if !amount.is_zero() {
+			T::Currency::hold(&HoldReason::RegionDeposit.into(), who, amount)?;
+			RegionDeposits::<T>::insert(region_id, (who.clone(), amount));
+		}
+		Ok(())
+	}
+
+	/// Release a deposit taken from `RegionDeposits`, if any, to whoever placed it.
+	pub(crate) fn refund_region_deposit(deposit: Option<(T::AccountId, BalanceOf<T>)>) {
+		if let Some((who, amount)) = deposit {
+			T::Currency::release(&HoldReason::RegionDeposit.into(), &who, amount, Exact)
+				.defensive_ok();
+		}
+	}
+
 	pub(crate) fn issue(
 		core: CoreIndex,
 		begin: Timeslice,
@@ -96,13 +223,18 @@ impl<T: Config> Pallet<T> {
 		if let Some(check_owner) = maybe_check_owner {
 			ensure!(check_owner == region.owner, Error::<T>::NotOwner);
 		}
+		ensure!(region_id.core < status.core_count, Error::<T>::CoreNoLongerExists);
 
 		Regions::<T>::remove(&region_id);
+		Listings::<T>::remove(&region_id);
+		RegionMetadata::<T>::remove(&region_id);
+		let deposit = RegionDeposits::<T>::take(&region_id);
 
 		let last_committed_timeslice = status.last_committed_timeslice;
 		if region_id.begin <= last_committed_timeslice {
 			region_id.begin = last_committed_timeslice + 1;
 			if region_id.begin >= region.end {
+				Self::refund_region_deposit(deposit);
 				let duration = region.end.saturating_sub(region_id.begin);
 				Self::deposit_event(Event::RegionDropped { region_id, duration });
 				return Ok(None)
@@ -114,8 +246,239 @@ impl<T: Config> Pallet<T> {
 		}
 		if finality == Finality::Provisional {
 			Regions::<T>::insert(&region_id, &region);
+			RegionDeposits::<T>::set(&region_id, deposit);
+		} else {
+			Self::refund_region_deposit(deposit);
 		}
 
 		Ok(Some((region_id, region)))
 	}
+
+	/// Note the assignment of `region_id` with `workplan` (the schedule of the Region's core at
+	/// its beginning) towards renewal of the core, provided the Region lasts a whole sale period
+	/// and was paid for. Once the whole core has been assigned it becomes renewable.
+	pub(crate) fn note_renewable(
+		config: &ConfigRecordOf<T>,
+		region_id: RegionId,
+		region: &RegionRecordOf<T>,
+		workplan: Schedule,
+	) {
+		let duration = region.end.saturating_sub(region_id.begin);
+		if duration != config.region_length {
+			return
+		}
+		let Some(price) = region.paid else { return };
+		let renewal_id = AllowedRenewalId { core: region_id.core, when: region.end };
+		let assigned = match AllowedRenewals::<T>::get(renewal_id) {
+			Some(AllowedRenewalRecord { completion: Partial(w), price: p }) if price == p => w,
+			_ => CoreMask::void(),
+		} | region_id.mask;
+		let workload = if assigned.is_complete() { Complete(workplan) } else { Partial(assigned) };
+		let record = AllowedRenewalRecord { price, completion: workload };
+		AllowedRenewals::<T>::insert(&renewal_id, &record);
+		if let Some(workload) = record.completion.drain_complete() {
+			Self::deposit_event(Event::Renewable {
+				core: region_id.core,
+				price,
+				begin: region.end,
+				workload,
+			});
+		}
+	}
+
+	/// Credit `amount`, already held by the pallet, to `beneficiary` on the Relay-chain and note
+	/// when it expires.
+	pub(crate) fn issue_credit(beneficiary: RelayAccountIdOf<T>, amount: BalanceOf<T>) {
+		T::Coretime::credit_account(beneficiary.clone(), T::ConvertBalance::convert(amount));
+		let expiry = Self::current_timeslice().saturating_add(T::CreditExpiry::get());
+		CreditExpiries::<T>::mutate(expiry, &beneficiary, |a| a.saturating_accrue(amount));
+		CreditOutstanding::<T>::mutate(&beneficiary, |a| a.saturating_accrue(amount));
+	}
+
+	/// Whether no two items of `schedule` share a part of the core.
+	pub(crate) fn is_disjoint(schedule: &Schedule) -> bool {
+		let mut used = CoreMask::void();
+		schedule.iter().all(|i| {
+			let disjoint = (used & i.mask).is_void();
+			used |= i.mask;
+			disjoint
+		})
+	}
+
+	/// Whether the items of `schedule` together cover the whole core.
+	pub(crate) fn is_complete(schedule: &Schedule) -> bool {
+		schedule.iter().fold(CoreMask::void(), |a, i| a | i.mask).is_complete()
+	}
+
+	/// Overlay `workplan` onto `workload`, dropping any items of `workload` which share a part of
+	/// the core with `workplan`.
+	pub(crate) fn merge_schedule(workplan: Schedule, workload: Schedule) -> Schedule {
+		let parts_used = workplan.iter().map(|i| i.mask).fold(CoreMask::void(), |a, i| a | i);
+		let mut workplan = workplan.into_inner();
+		workplan.extend(workload.into_iter().filter(|i| (i.mask & parts_used).is_void()));
+		Schedule::truncate_from(workplan)
+	}
+
+	/// Convert `schedule` into the assignment given to the Relay-chain, with any unused parts of
+	/// the core left idle.
+	pub(crate) fn schedule_to_assignment(
+		schedule: Schedule,
+	) -> Vec<(CoreAssignment, PartsOf57600)> {
+		let mut total_used = 0;
+		let mut intermediate = schedule
+			.into_iter()
+			.map(|i| (i.assignment, i.mask.count_ones() as u16 * (57_600 / 80)))
+			.inspect(|i| total_used.saturating_accrue(i.1))
+			.collect::<Vec<_>>();
+		if total_used < 57_600 {
+			intermediate.push((CoreAssignment::Idle, 57_600 - total_used));
+		}
+		intermediate.sort();
+		let mut assignment: Vec<(CoreAssignment, PartsOf57600)> =
+			Vec::with_capacity(intermediate.len());
+		for i in intermediate.into_iter() {
+			if let Some(ref mut last) = assignment.last_mut() {
+				if last.0 == i.0 {
+					last.1 += i.1;
+					continue
+				}
+			}
+			assignment.push(i);
+		}
+		assignment
+	}
+
+	/// The assignment of `core` in effect at timeslice `when`, given the current workload and
+	/// all workplans yet to be processed.
+	///
+	/// For a timeslice with a workplan this is exactly the assignment which will be given to the
+	/// Relay-chain once it is committed. Timeslices which have already been committed yield the
+	/// current workload.
+	pub fn effective_assignment(
+		core: CoreIndex,
+		when: Timeslice,
+	) -> Vec<(CoreAssignment, PartsOf57600)> {
+		let mut workplans = Workplan::<T>::iter()
+			.filter(|((timeslice, c), _)| *c == core && *timeslice <= when)
+			.collect::<Vec<_>>();
+		workplans.sort_by_key(|((timeslice, _), _)| *timeslice);
+		let schedule =
+			workplans.into_iter().fold(Workload::<T>::get(core), |workload, (_, workplan)| {
+				Self::merge_schedule(workplan, workload)
+			});
+		Self::schedule_to_assignment(schedule)
+	}
+
+	/// The assignment of `core` for each of the next `cycles` sale cycles, beginning with the
+	/// Regions of the ongoing sale, together with the timeslice at which each cycle begins.
+	///
+	/// Cycles yet to be initialized are projected from the current reservations and leases, in the
+	/// order in which they are placed on cores when a sale is rotated. Any other core is taken to
+	/// keep the assignment in effect for it then.
+	pub fn schedule_for_core(
+		core: CoreIndex,
+		cycles: u32,
+	) -> Vec<(Timeslice, Vec<(CoreAssignment, PartsOf57600)>)> {
+		let (Some(config), Some(sale)) = (Configuration::<T>::get(), SaleInfo::<T>::get()) else {
+			return Vec::new()
+		};
+		let reservations = Reservations::<T>::get();
+		let leases = Leases::<T>::get();
+		(0..cycles)
+			.map(|i| {
+				let begin =
+					sale.region_begin.saturating_add(i.saturating_mul(config.region_length));
+				if i == 0 {
+					// The workplan of the ongoing sale has already been written.
+					return (begin, Self::effective_assignment(core, begin))
+				}
+				let index = core as usize;
+				let projected = match reservations.get(index) {
+					Some(schedule) => Some(schedule.clone()),
+					None => leases
+						.iter()
+						.filter(|lease| lease.until >= begin)
+						.nth(index - reservations.len())
+						.map(|lease| {
+							let item = ScheduleItem {
+								mask: CoreMask::complete(),
+								assignment: CoreAssignment::Task(lease.task),
+							};
+							Schedule::truncate_from(vec![item])
+						}),
+				};
+				let assignment = match projected {
+					Some(schedule) => Self::schedule_to_assignment(schedule),
+					None => Self::effective_assignment(core, begin),
+				};
+				(begin, assignment)
+			})
+			.collect()
+	}
+
+	/// Ensure the consistency of the pallet's storage:
+	///
+	/// * Every Region, held or pooled, has a non-empty span and part of its core, and no two of
+	///   them share a part of the same core for any timeslice.
+	/// * No Region is both held and contributed to the Instantaneous Coretime Pool.
+	/// * Every unclaimed payout of the Instantaneous Coretime Pool has private contributions to be
+	///   claimed by.
+	/// * The pot holds at least all unclaimed payouts.
+	/// * The deposits of all Regions are held from whoever placed them.
+	#[cfg(any(feature = "try-runtime", test))]
+	pub(crate) fn do_try_state() -> Result<(), sp_runtime::TryRuntimeError> {
+		let mut spans = Vec::new();
+		for (region_id, region) in Regions::<T>::iter() {
+			// A Region pooled with `Provisional` finality is still held, but only for as long as
+			// it is contributed.
+			if let Some(contribution) = InstaPoolContribution::<T>::get(&region_id) {
+				ensure!(
+					region_id.begin.saturating_add(contribution.length) == region.end,
+					"A Region is both held and pooled for different spans"
+				);
+			}
+			spans.push((region_id, region.end, true));
+		}
+		for (region_id, contribution) in InstaPoolContribution::<T>::iter() {
+			spans.push((region_id, region_id.begin.saturating_add(contribution.length), false));
+		}
+		spans.sort_by_key(|(region_id, _, _)| (region_id.core, region_id.begin));
+		for (i, (region_id, end, held)) in spans.iter().enumerate() {
+			ensure!(region_id.begin < *end, "A Region has an empty span");
+			ensure!(!region_id.mask.is_void(), "A Region has no part of its core");
+			for (other_id, other_end, other_held) in spans[i + 1..]
+				.iter()
+				.take_while(|(o, _, _)| o.core == region_id.core && o.begin < *end)
+			{
+				// The contribution of a provisionally pooled Region overlaps the Region itself.
+				let provisional =
+					held != other_held && region_id.mask == other_id.mask && end == other_end;
+				ensure!(
+					provisional || (region_id.mask & other_id.mask).is_void(),
+					"Regions share a part of a core"
+				);
+			}
+		}
+
+		let mut owed = BalanceOf::<T>::zero();
+		for (_, record) in InstaPoolHistory::<T>::iter() {
+			if let Some(payout) = record.maybe_payout {
+				ensure!(record.private_contributions > 0, "A payout has no contributions");
+				owed.saturating_accrue(payout);
+			}
+		}
+		let pot = T::Currency::balance(&Self::account_id());
+		ensure!(pot >= owed, "The pot does not cover payouts owed");
+
+		let mut deposits = sp_std::collections::btree_map::BTreeMap::new();
+		for (_, (who, amount)) in RegionDeposits::<T>::iter() {
+			deposits.entry(who).or_default().saturating_accrue(amount);
+		}
+		for (who, amount) in deposits {
+			let reason = HoldReason::RegionDeposit.into();
+			let held = <T::Currency as fungible::InspectHold<_>>::balance_on_hold(&reason, &who);
+			ensure!(held >= amount, "A Region deposit is not held");
+		}
+		Ok(())
+	}
 }
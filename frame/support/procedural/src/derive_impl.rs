@@ -19,11 +19,12 @@
 
 use derive_syn_parse::Parse;
 use macro_magic::mm_core::ForeignPath;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
 use quote::{quote, ToTokens};
 use std::collections::HashSet;
 use syn::{
-	parse2, parse_quote, spanned::Spanned, token, Ident, ImplItem, ItemImpl, Path, Result, Token,
+	parse2, parse_quote, spanned::Spanned, token, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl,
+	Pat, Path, Result, Token,
 };
 
 mod keyword {
@@ -53,8 +54,36 @@ where
 	item.attrs.get(0).map(|a| syn::parse2(a.into_token_stream())).transpose()
 }
 
+/// Parses the `default_impl_path` of [`DeriveImplAttrArgs`], making sure it can actually name an
+/// impl registered with `#[register_default_impl(..)]`.
+///
+/// Such a path cannot have generic arguments, and cannot consist of a bare `crate`, `self`,
+/// `super` or `Self`, as the path is both used to look up the exported tokens and to qualify the
+/// filled in items.
+fn parse_default_impl_path(input: syn::parse::ParseStream) -> Result<Path> {
+	let path = input.parse::<Path>()?;
+	if let Some(segment) = path.segments.iter().find(|segment| !segment.arguments.is_empty()) {
+		return Err(syn::Error::new_spanned(
+			&segment.arguments,
+			"the default impl path of `derive_impl` cannot have generic arguments",
+		))
+	}
+	let Some(last) = path.segments.last() else {
+		return Err(syn::Error::new_spanned(&path, "the default impl path cannot be empty"))
+	};
+	if ["crate", "self", "super", "Self"].iter().any(|keyword| last.ident == keyword) {
+		return Err(syn::Error::new_spanned(
+			&path,
+			"the default impl path of `derive_impl` must name an impl registered with \
+			`#[register_default_impl(..)]`",
+		))
+	}
+	Ok(path)
+}
+
 #[derive(Parse, Debug)]
 pub struct DeriveImplAttrArgs {
+	#[call(parse_default_impl_path)]
 	pub default_impl_path: Path,
 	_as: Option<Token![as]>,
 	#[parse_if(_as.is_some())]
@@ -95,6 +124,124 @@ fn impl_item_ident(impl_item: &ImplItem) -> Option<&Ident> {
 	}
 }
 
+/// Gets the [`Ident`] of the given local [`ImplItem`] if it is marked with `#[no_default]`.
+/// Otherwise returns [`None`].
+///
+/// Such items only serve to keep the item of the same name from being filled in from the default
+/// impl and are removed from the final impl. Besides items with a value, this accepts type
+/// declarations without one, i.e. `#[no_default] type Foo;`.
+fn no_default_item_ident(impl_item: &ImplItem) -> Option<Ident> {
+	let is_no_default =
+		|attrs: &[syn::Attribute]| attrs.iter().any(|attr| attr.path().is_ident("no_default"));
+	match impl_item {
+		ImplItem::Const(item) if is_no_default(&item.attrs) => Some(item.ident.clone()),
+		ImplItem::Fn(item) if is_no_default(&item.attrs) => Some(item.sig.ident.clone()),
+		ImplItem::Type(item) if is_no_default(&item.attrs) => Some(item.ident.clone()),
+		ImplItem::Verbatim(tokens) => parse2::<syn::TraitItemType>(tokens.clone())
+			.ok()
+			.filter(|item| is_no_default(&item.attrs))
+			.map(|item| item.ident),
+		_ => None,
+	}
+}
+
+/// Gets the [`Ident`]s of the items of `local_impl` which shadow an item of the same name in
+/// `foreign_impl`. Items marked with `#[no_default]` are left out, as these state their intent
+/// explicitly.
+fn shadowing_item_idents<'a>(local_impl: &'a ItemImpl, foreign_impl: &ItemImpl) -> Vec<&'a Ident> {
+	let foreign_keys: HashSet<&Ident> =
+		foreign_impl.items.iter().filter_map(impl_item_ident).collect();
+	local_impl
+		.items
+		.iter()
+		.filter(|item| no_default_item_ident(item).is_none())
+		.filter_map(impl_item_ident)
+		.filter(|ident| foreign_keys.contains(ident))
+		.collect()
+}
+
+/// Builds a warning for each item of `local_impl` shadowing a default of `foreign_impl`, pointing
+/// at the local item.
+///
+/// These are only emitted when `FRAME_EXPAND` is set, as overriding defaults is what
+/// `derive_impl` is for; the warnings merely help to understand what the macro did.
+fn shadowing_warnings(
+	local_impl: &ItemImpl,
+	foreign_impl: &ItemImpl,
+	default_impl_path: &Path,
+) -> Vec<proc_macro_warning::Warning> {
+	if std::env::var("FRAME_EXPAND").is_err() {
+		return Vec::new()
+	}
+	let default_impl_path = default_impl_path.to_token_stream().to_string().replace(' ', "");
+	shadowing_item_idents(local_impl, foreign_impl)
+		.into_iter()
+		.enumerate()
+		.map(|(index, ident)| {
+			proc_macro_warning::Warning::new_deprecated("DeriveImplShadowedDefault")
+				.index(index)
+				.old(format!("shadow the default `{}` of `{}`", ident, default_impl_path))
+				.new("make sure that overriding this default is intended")
+				.help_link("https://paritytech.github.io/substrate/master/frame_support/attr.derive_impl.html")
+				.span(ident.span())
+				.build()
+		})
+		.collect()
+}
+
+/// Returns `true` if the given tokens mention `Self` anywhere.
+fn mentions_self(tokens: TokenStream2) -> bool {
+	tokens.into_iter().any(|token| match token {
+		TokenTree::Ident(ident) => ident == "Self",
+		TokenTree::Group(group) => mentions_self(group.stream()),
+		_ => false,
+	})
+}
+
+/// Builds a `fn` item with the signature of the given default `fn` whose body simply calls
+/// `<#default_impl_path as #disambiguation_path>::#ident(..)`, so that the body of the default does
+/// not need to resolve in the scope of the local impl.
+///
+/// Returns [`None`] if the call cannot be forwarded as-is (i.e. the `fn` takes a receiver, refers
+/// to `Self` in its signature, is generic, `async` or `unsafe`, or destructures any of its
+/// arguments), in which case the caller should fall back to copying the item verbatim.
+fn delegate_fn_item(
+	item: &ImplItemFn,
+	default_impl_path: &Path,
+	disambiguation_path: &Path,
+) -> Option<ImplItem> {
+	let mut sig = item.sig.clone();
+	if !sig.generics.params.is_empty() ||
+		sig.asyncness.is_some() ||
+		sig.unsafety.is_some() ||
+		sig.variadic.is_some() ||
+		mentions_self(sig.to_token_stream())
+	{
+		return None
+	}
+	let mut args = Vec::with_capacity(sig.inputs.len());
+	for input in sig.inputs.iter_mut() {
+		let FnArg::Typed(typed) = input else { return None };
+		match &mut *typed.pat {
+			Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => {
+				// `mut` is not needed when only forwarding the argument.
+				pat.mutability = None;
+				args.push(pat.ident.clone());
+			},
+			_ => return None,
+		}
+	}
+	let attrs = &item.attrs;
+	let vis = &item.vis;
+	let ident = &sig.ident;
+	Some(parse_quote! {
+		#(#attrs)*
+		#vis #sig {
+			<#default_impl_path as #disambiguation_path>::#ident(#(#args),*)
+		}
+	})
+}
+
 /// The real meat behind `derive_impl`. Takes in a `local_impl`, which is the impl for which we
 /// want to implement defaults (i.e. the one the attribute macro is attached to), and a
 /// `foreign_impl`, which is the impl containing the defaults we want to use, and returns an
@@ -102,18 +249,35 @@ fn impl_item_ident(impl_item: &ImplItem) -> Option<&Ident> {
 ///
 /// This process has the following caveats:
 /// * Colliding items that have an ident are not copied into `local_impl`
+/// * Items of `local_impl` marked with `#[no_default]` are removed, and the items of the same name
+///   are not copied into `local_impl` either
 /// * Uncolliding items that have an ident are copied into `local_impl` but are qualified as `type
-///   #ident = <#default_impl_path as #disambiguation_path>::#ident;`
+///   #ident = <#default_impl_path as #disambiguation_path>::#ident;`, keeping the attributes of the
+///   default item (such as `#[cfg]`). Default types referring to one another through `Self` hence
+///   keep resolving against the default impl, regardless of the order they are declared in
+/// * Uncolliding `const` items are likewise qualified as `const #ident: #ty = <#default_impl_path
+///   as #disambiguation_path>::#ident;` unless `#ty` refers to `Self`
+/// * Uncolliding `fn` items are copied into `local_impl` as a forwarding call to
+///   `<#default_impl_path as #disambiguation_path>::#ident(..)` where the signature allows it, and
+///   verbatim otherwise
 /// * Items that lack an ident are de-duplicated so only unique items that lack an ident are copied
 ///   into `local_impl`. Items that lack an ident and also exist verbatim in `local_impl` are not
 ///   copied over.
 fn combine_impls(
-	local_impl: ItemImpl,
+	mut local_impl: ItemImpl,
 	foreign_impl: ItemImpl,
 	default_impl_path: Path,
 	disambiguation_path: Path,
 	inject_runtime_types: bool,
 ) -> ItemImpl {
+	let mut no_default_keys = HashSet::new();
+	local_impl.items.retain(|item| match no_default_item_ident(item) {
+		Some(ident) => {
+			no_default_keys.insert(ident);
+			false
+		},
+		None => true,
+	});
 	let (existing_local_keys, existing_unsupported_items): (HashSet<ImplItem>, HashSet<ImplItem>) =
 		local_impl
 			.items
@@ -123,6 +287,7 @@ fn combine_impls(
 	let existing_local_keys: HashSet<Ident> = existing_local_keys
 		.into_iter()
 		.filter_map(|item| impl_item_ident(&item).cloned())
+		.chain(no_default_keys)
 		.collect();
 	let mut final_impl = local_impl;
 	let extended_items = foreign_impl.items.into_iter().filter_map(|item| {
@@ -137,7 +302,12 @@ fn combine_impls(
 					get_first_item_pallet_attr::<PalletAttr>(&mut typ)
 				{
 					let item: ImplItem = if inject_runtime_types {
+						// keep the attributes of the default item, except for the marker itself
+						// which need not be in scope of the local impl
+						typ.attrs.remove(0);
+						let attrs = &typ.attrs;
 						parse_quote! {
+							#(#attrs)*
 							type #ident = #ident;
 						}
 					} else {
@@ -145,13 +315,34 @@ fn combine_impls(
 					};
 					return Some(item)
 				}
-				// modify and insert uncolliding type items
+				// modify and insert uncolliding type items, keeping their attributes
+				let attrs = &typ.attrs;
 				let modified_item: ImplItem = parse_quote! {
+					#(#attrs)*
 					type #ident = <#default_impl_path as #disambiguation_path>::#ident;
 				};
 				return Some(modified_item)
 			}
-			// copy uncolliding non-type items that have an ident
+			if let ImplItem::Const(konst) = &item {
+				// qualify uncolliding const items unless their type depends on `Self`
+				if !mentions_self(konst.ty.to_token_stream()) {
+					let (attrs, vis, ty) = (&konst.attrs, &konst.vis, &konst.ty);
+					let modified_item: ImplItem = parse_quote! {
+						#(#attrs)*
+						#vis const #ident: #ty = <#default_impl_path as #disambiguation_path>::#ident;
+					};
+					return Some(modified_item)
+				}
+			}
+			if let ImplItem::Fn(func) = &item {
+				// forward uncolliding fn items to the default impl where possible
+				if let Some(delegated) =
+					delegate_fn_item(func, &default_impl_path, &disambiguation_path)
+				{
+					return Some(delegated)
+				}
+			}
+			// copy remaining uncolliding non-type items that have an ident
 			Some(item)
 		} else {
 			// do not copy colliding items that lack an ident
@@ -199,6 +390,8 @@ pub fn derive_impl(
 			)),
 	};
 
+	let warnings = shadowing_warnings(&local_impl, &foreign_impl, &default_impl_path);
+
 	// generate the combined impl
 	let combined_impl = combine_impls(
 		local_impl,
@@ -208,7 +401,16 @@ pub fn derive_impl(
 		no_aggregated_types.is_none(),
 	);
 
-	Ok(quote!(#combined_impl))
+	if warnings.is_empty() {
+		return Ok(quote!(#combined_impl))
+	}
+	Ok(quote! {
+		#combined_impl
+
+		const _: () = {
+			#(#warnings)*
+		};
+	})
 }
 
 #[test]
@@ -226,4 +428,8 @@ fn test_derive_impl_attr_args_parsing() {
 	parse2::<DeriveImplAttrArgs>(quote!(DefaultConfig)).unwrap();
 	assert!(parse2::<DeriveImplAttrArgs>(quote!()).is_err());
 	assert!(parse2::<DeriveImplAttrArgs>(quote!(Config Config)).is_err());
+	assert!(parse2::<DeriveImplAttrArgs>(quote!(Something<u32> as DefaultConfig)).is_err());
+	assert!(parse2::<DeriveImplAttrArgs>(quote!(some<u32>::Something)).is_err());
+	assert!(parse2::<DeriveImplAttrArgs>(quote!(crate as DefaultConfig)).is_err());
+	assert!(parse2::<DeriveImplAttrArgs>(quote!(super::super)).is_err());
 }
@@ -746,6 +746,24 @@ pub fn storage_alias(attributes: TokenStream, input: TokenStream) -> TokenStream
 /// local impl will be retained. All imported items are qualified by the `disambiguation_path`, as
 /// discussed above.
 ///
+/// When the `FRAME_EXPAND` environment variable is set during compilation, a warning is emitted for
+/// each item of the local impl that shadows an item of the default impl, naming the item.
+///
+/// ## Opting Out of Default Items
+///
+/// An item of the local impl can be marked with `#[no_default]` to keep the item of the same name
+/// from being filled in from the default impl. The marked item itself is removed from the
+/// expansion, so the trait's own default is used instead, or compilation fails if there is none.
+/// For types a bare declaration suffices:
+///
+/// ```ignore
+/// #[derive_impl(TestDefaultConfig as pallet::DefaultConfig)]
+/// impl pallet::Config for Test {
+/// 	#[no_default]
+/// 	type Currency;
+/// }
+/// ```
+///
 /// ## Handling of Unnamed Trait Items
 ///
 /// Items that lack a `syn::Ident` for whatever reason are first checked to see if they exist,
@@ -32,7 +32,14 @@ fn derive_impl_ui() {
 	// Deny all warnings since we emit warnings as part of a Pallet's UI.
 	std::env::set_var("RUSTFLAGS", "--deny warnings");
 
+	{
+		let t = trybuild::TestCases::new();
+		t.compile_fail("tests/derive_impl_ui/*.rs");
+		t.pass("tests/derive_impl_ui/pass/*.rs");
+	}
+
+	// Shadowed defaults are only reported when expanding for debugging.
+	std::env::set_var("FRAME_EXPAND", "1");
 	let t = trybuild::TestCases::new();
-	t.compile_fail("tests/derive_impl_ui/*.rs");
-	t.pass("tests/derive_impl_ui/pass/*.rs");
+	t.compile_fail("tests/derive_impl_ui/warnings/*.rs");
 }
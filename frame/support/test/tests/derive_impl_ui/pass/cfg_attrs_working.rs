@@ -0,0 +1,36 @@
+use frame_support::*;
+use static_assertions::assert_type_eq_all;
+
+pub trait Animal {
+	type Diet;
+	#[cfg(feature = "frame-feature-testing")]
+	type Tail;
+}
+
+pub type Omnivore = char;
+
+mod defaults {
+	use super::*;
+
+	pub struct FourLeggedAnimal {}
+
+	#[register_default_impl(FourLeggedAnimal)]
+	impl Animal for FourLeggedAnimal {
+		/// What the animal eats.
+		type Diet = Omnivore;
+		// Only exists when the feature is enabled, and must not be filled in otherwise.
+		#[cfg(feature = "frame-feature-testing")]
+		type Tail = ();
+	}
+}
+
+pub struct Lion {}
+
+#[derive_impl(defaults::FourLeggedAnimal)]
+impl Animal for Lion {}
+
+assert_type_eq_all!(<Lion as Animal>::Diet, Omnivore);
+#[cfg(feature = "frame-feature-testing")]
+assert_type_eq_all!(<Lion as Animal>::Tail, ());
+
+fn main() {}
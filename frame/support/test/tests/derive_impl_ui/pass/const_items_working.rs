@@ -0,0 +1,34 @@
+use frame_support::*;
+
+pub trait Animal {
+	const NAME: &'static str;
+	const LEGS: usize;
+}
+
+mod defaults {
+	use super::*;
+
+	// Only reachable from within this module, hence the default `LEGS` must be read through
+	// `FourLeggedAnimal` rather than copied.
+	const FOUR: usize = 4;
+
+	pub struct FourLeggedAnimal {}
+
+	#[register_default_impl(FourLeggedAnimal)]
+	impl Animal for FourLeggedAnimal {
+		const NAME: &'static str = "A Four-Legged Animal";
+		const LEGS: usize = FOUR;
+	}
+}
+
+pub struct Lion {}
+
+#[derive_impl(defaults::FourLeggedAnimal)]
+impl Animal for Lion {
+	const NAME: &'static str = "Lion";
+}
+
+fn main() {
+	assert_eq!(<Lion as Animal>::NAME, "Lion");
+	assert_eq!(<Lion as Animal>::LEGS, 4);
+}
@@ -0,0 +1,45 @@
+use frame_support::*;
+use static_assertions::assert_type_eq_all;
+use std::collections::BTreeMap;
+
+pub trait Store {
+	type Key: Ord;
+	type Value: Default;
+	type Map: Default + Extend<(Self::Key, Self::Value)>;
+
+	fn name() -> &'static str;
+}
+
+pub struct DefaultStore {}
+
+// The default types refer to one another through `Self`.
+#[register_default_impl(DefaultStore)]
+impl Store for DefaultStore {
+	type Key = u32;
+	type Value = Self::Key;
+	type Map = BTreeMap<Self::Key, Self::Value>;
+
+	fn name() -> &'static str {
+		"A Default Store"
+	}
+}
+
+pub struct Ledger {}
+
+#[derive_impl(DefaultStore as Store)]
+impl Store for Ledger {
+	fn name() -> &'static str {
+		"A Ledger"
+	}
+}
+
+assert_type_eq_all!(<Ledger as Store>::Key, u32);
+assert_type_eq_all!(<Ledger as Store>::Value, u32);
+assert_type_eq_all!(<Ledger as Store>::Map, BTreeMap<u32, u32>);
+
+fn main() {
+	let mut map = <Ledger as Store>::Map::default();
+	map.extend([(1, 2)]);
+	assert_eq!(map.get(&1), Some(&2));
+	assert_eq!(<Ledger as Store>::name(), "A Ledger");
+}
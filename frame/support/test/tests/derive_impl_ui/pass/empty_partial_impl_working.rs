@@ -0,0 +1,39 @@
+use frame_support::*;
+use static_assertions::assert_type_eq_all;
+
+pub trait Animal {
+	type Locomotion;
+	type Diet;
+	const LEGS: usize;
+	fn animal_name() -> &'static str;
+}
+
+pub type RunsOnFourLegs = (usize, usize, usize, usize);
+pub type Omnivore = char;
+
+pub struct FourLeggedAnimal {}
+
+#[register_default_impl(FourLeggedAnimal)]
+impl Animal for FourLeggedAnimal {
+	type Locomotion = RunsOnFourLegs;
+	type Diet = Omnivore;
+	const LEGS: usize = 4;
+
+	fn animal_name() -> &'static str {
+		"A Four-Legged Animal"
+	}
+}
+
+pub struct Dog {}
+
+// an empty impl takes every item from the default impl
+#[derive_impl(FourLeggedAnimal)]
+impl Animal for Dog {}
+
+assert_type_eq_all!(<Dog as Animal>::Locomotion, RunsOnFourLegs);
+assert_type_eq_all!(<Dog as Animal>::Diet, Omnivore);
+
+fn main() {
+	assert_eq!(<Dog as Animal>::LEGS, 4);
+	assert_eq!(<Dog as Animal>::animal_name(), "A Four-Legged Animal");
+}
@@ -0,0 +1,52 @@
+use frame_support::*;
+use static_assertions::assert_type_eq_all;
+
+pub trait Animal {
+	type Diet;
+
+	fn animal_name() -> &'static str;
+	fn legs(limbs: usize) -> usize;
+}
+
+pub type Omnivore = char;
+pub type Carnivore = (char, char);
+
+mod defaults {
+	use super::*;
+
+	// Only reachable from within this module, hence the default `legs` must be called through
+	// `FourLeggedAnimal` rather than copied.
+	fn arms(limbs: usize) -> usize {
+		limbs / 2
+	}
+
+	pub struct FourLeggedAnimal {}
+
+	#[register_default_impl(FourLeggedAnimal)]
+	impl Animal for FourLeggedAnimal {
+		type Diet = Omnivore;
+
+		fn animal_name() -> &'static str {
+			"A Four-Legged Animal"
+		}
+
+		fn legs(mut limbs: usize) -> usize {
+			limbs -= arms(limbs);
+			limbs
+		}
+	}
+}
+
+pub struct Lion {}
+
+#[derive_impl(defaults::FourLeggedAnimal)]
+impl Animal for Lion {
+	type Diet = Carnivore;
+}
+
+assert_type_eq_all!(<Lion as Animal>::Diet, Carnivore);
+
+fn main() {
+	assert_eq!(<Lion as Animal>::animal_name(), "A Four-Legged Animal");
+	assert_eq!(<Lion as Animal>::legs(8), 4);
+}
@@ -0,0 +1,43 @@
+use frame_support::*;
+use static_assertions::assert_type_eq_all;
+
+pub trait Animal {
+	type Locomotion;
+	type Diet;
+
+	fn animal_name() -> &'static str;
+}
+
+pub type RunsOnFourLegs = (usize, usize, usize, usize);
+pub type Omnivore = char;
+
+pub struct FourLeggedAnimal {}
+
+#[register_default_impl(FourLeggedAnimal)]
+impl Animal for FourLeggedAnimal {
+	type Locomotion = RunsOnFourLegs;
+	type Diet = Omnivore;
+
+	fn animal_name() -> &'static str {
+		"A Four-Legged Animal"
+	}
+}
+
+pub struct Pet<Food>(core::marker::PhantomData<Food>);
+
+// The generics and where-clause of the local impl are kept for all items filled in from the
+// default impl.
+#[derive_impl(FourLeggedAnimal as Animal)]
+impl<Food: Clone> Animal for Pet<Food>
+where
+	Food: Default + 'static,
+{
+	type Diet = Food;
+}
+
+assert_type_eq_all!(<Pet<u8> as Animal>::Diet, u8);
+assert_type_eq_all!(<Pet<u8> as Animal>::Locomotion, RunsOnFourLegs);
+
+fn main() {
+	assert_eq!(<Pet<u8> as Animal>::animal_name(), "A Four-Legged Animal");
+}
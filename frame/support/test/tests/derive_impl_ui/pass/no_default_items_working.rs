@@ -0,0 +1,40 @@
+use frame_support::*;
+
+pub trait Animal {
+	fn animal_name() -> &'static str {
+		"An Unknown Animal"
+	}
+	fn legs() -> usize;
+}
+
+mod defaults {
+	use super::*;
+
+	pub struct FourLeggedAnimal {}
+
+	#[register_default_impl(FourLeggedAnimal)]
+	impl Animal for FourLeggedAnimal {
+		fn animal_name() -> &'static str {
+			"A Four-Legged Animal"
+		}
+
+		fn legs() -> usize {
+			4
+		}
+	}
+}
+
+pub struct Lion {}
+
+#[derive_impl(defaults::FourLeggedAnimal)]
+impl Animal for Lion {
+	#[no_default]
+	fn animal_name() -> &'static str {
+		unreachable!()
+	}
+}
+
+fn main() {
+	assert_eq!(<Lion as Animal>::animal_name(), "An Unknown Animal");
+	assert_eq!(<Lion as Animal>::legs(), 4);
+}
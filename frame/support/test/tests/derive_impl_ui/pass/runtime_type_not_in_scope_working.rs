@@ -0,0 +1,32 @@
+use frame_support::derive_impl;
+use static_assertions::assert_type_eq_all;
+
+pub trait Config {
+	type RuntimeCall;
+}
+
+type RuntimeCall = u32;
+
+mod defaults {
+	use super::Config;
+	use frame_support::{pallet_prelude::inject_runtime_type, register_default_impl};
+
+	pub struct Pallet;
+
+	#[register_default_impl(Pallet)]
+	impl Config for Pallet {
+		/// The overarching call type.
+		#[inject_runtime_type]
+		type RuntimeCall = ();
+	}
+}
+
+struct SomePallet;
+
+// `inject_runtime_type` is not in scope here, so it must not be copied over.
+#[derive_impl(defaults::Pallet)]
+impl Config for SomePallet {}
+
+assert_type_eq_all!(<SomePallet as Config>::RuntimeCall, u32);
+
+fn main() {}
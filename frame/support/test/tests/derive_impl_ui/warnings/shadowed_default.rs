@@ -0,0 +1,30 @@
+use frame_support::*;
+
+pub trait Animal {
+	type Locomotion;
+	type Diet;
+
+	fn animal_name() -> &'static str;
+}
+
+pub struct FourLeggedAnimal {}
+
+#[register_default_impl(FourLeggedAnimal)]
+impl Animal for FourLeggedAnimal {
+	type Locomotion = (usize, usize, usize, usize);
+	type Diet = char;
+
+	fn animal_name() -> &'static str {
+		"A Four-Legged Animal"
+	}
+}
+
+pub struct Snake {}
+
+// `Locomotion` shadows the default, which is reported when `FRAME_EXPAND` is set.
+#[derive_impl(FourLeggedAnimal as Animal)]
+impl Animal for Snake {
+	type Locomotion = ();
+}
+
+fn main() {}